@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct AudioArgs {
+    /// .chart file to be used, in its song folder
+    input_file: PathBuf,
+
+    /// Point missing streams at files present under a similar name, and write the chart
+    #[arg(short, long)]
+    relink: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// Folder the chart is in, which its audio paths are relative to.
+fn song_folder(input_file: &Path) -> &Path {
+    match input_file.parent() {
+        Some(folder) if !folder.as_os_str().is_empty() => folder,
+        _ => Path::new("."),
+    }
+}
+
+/// Names of the files next to the chart.
+fn folder_files(folder: &Path) -> Result<Vec<String>, Error> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(files)
+}
+
+pub(crate) fn run(args: &AudioArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    let folder = song_folder(&args.input_file);
+    let files = folder_files(folder)?;
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+    // joining an absolute path replaces the folder
+    let exists = |path: &str| folder.join(path).is_file();
+    let report = if args.relink {
+        chart.relink_audio(&files, exists)
+    } else {
+        chart.check_audio(&files, exists)
+    };
+    eprint!("{report}");
+    if args.relink {
+        args.output.write(&args.input_file, &chart.to_string())?;
+    }
+    let missing = report
+        .missing
+        .iter()
+        .filter(|missing| !args.relink || missing.replacement.is_none())
+        .count();
+    if missing > 0 {
+        return Err(Error::MissingAudio(missing));
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+use chart_file_parser::Chart;
+use clap::{Args, ValueEnum};
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+    Chart,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// Guess the format from the file extension, defaulting to .chart.
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Format::Json,
+            Some("yaml" | "yml") => Format::Yaml,
+            _ => Format::Chart,
+        }
+    }
+
+    pub(crate) fn read<'a>(self, text: &'a str) -> Result<Chart<'a>, Error> {
+        match self {
+            Format::Chart => parse_chart(text),
+            Format::Json => Ok(serde_json::from_str(text)?),
+            Format::Yaml => Ok(serde_yaml::from_str(text)?),
+        }
+    }
+
+    pub(crate) fn write(self, chart: &Chart) -> Result<String, Error> {
+        match self {
+            Format::Chart => Ok(chart.to_string()),
+            Format::Json => Ok(serde_json::to_string_pretty(chart)?),
+            Format::Yaml => Ok(serde_yaml::to_string(chart)?),
+        }
+    }
+}
+
+#[derive(Args)]
+pub(crate) struct ConvertArgs {
+    /// .chart, .json or .yaml file to be used
+    input_file: PathBuf,
+
+    /// Format of the input, guessed from the file extension by default
+    #[arg(long, value_enum)]
+    from: Option<Format>,
+
+    /// Format of the output, JSON for .chart input and .chart otherwise by default
+    #[arg(long, value_enum)]
+    to: Option<Format>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &ConvertArgs) -> Result<(), Error> {
+    let from = args
+        .from
+        .unwrap_or_else(|| Format::from_path(&args.input_file));
+    let to = args.to.unwrap_or(match from {
+        Format::Chart => Format::Json,
+        Format::Json | Format::Yaml => Format::Chart,
+    });
+    if args.output.in_place() && from != to {
+        return Err(Error::InPlaceConvert(from, to));
+    }
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let chart = from.read(&text)?;
+    args.output.write(&args.input_file, &to.write(&chart)?)
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use chart_file_parser::Tick;
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct CropArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// First section to keep
+    #[arg(
+        long,
+        conflicts_with_all = ["start", "end"],
+        required_unless_present_any = ["start", "end"]
+    )]
+    from_section: Option<String>,
+
+    /// Last section to keep (defaults to the first one)
+    #[arg(long, requires = "from_section")]
+    to_section: Option<String>,
+
+    /// First tick to keep (defaults to the start of the chart)
+    #[arg(long)]
+    start: Option<u32>,
+
+    /// Tick at which to stop (defaults to the end of the chart)
+    #[arg(long)]
+    end: Option<u32>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &CropArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let chart = parse_chart(&text)?;
+    let range = if let Some(first) = &args.from_section {
+        let last = args.to_section.as_ref().unwrap_or(first);
+        chart
+            .section_range(first, last)
+            .ok_or_else(|| Error::Section(first.clone(), last.clone()))?
+    } else {
+        let end = args
+            .end
+            .map_or_else(|| chart.last_tick().saturating_add(1), Tick::new);
+        Tick::new(args.start.unwrap_or_default())..end
+    };
+    let crop = chart.crop(range);
+    eprintln!(
+        "Start tick: {}, audio start: {:.3} s",
+        crop.origin, crop.audio_start
+    );
+    args.output.write(&args.input_file, &crop.chart.to_string())
+}
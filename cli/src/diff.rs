@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct DiffArgs {
+    /// Original .chart file
+    old_file: PathBuf,
+
+    /// Changed .chart file
+    new_file: PathBuf,
+
+    /// Print the differences as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub(crate) fn run(args: &DiffArgs) -> Result<(), Error> {
+    let old_text = std::fs::read_to_string(&args.old_file)?;
+    let new_text = std::fs::read_to_string(&args.new_file)?;
+    let old = parse_chart(&old_text)?;
+    let new = parse_chart(&new_text)?;
+    let diff = old.diff(&new)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{diff}");
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct InfoArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Print the statistics as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub(crate) fn run(args: &InfoArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let chart = parse_chart(&text)?;
+    let stats = chart.stats();
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print!("{stats}");
+    }
+    Ok(())
+}
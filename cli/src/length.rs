@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+
+use chart_file_parser::{duration::audio_duration, AudioStream};
+use clap::Args;
+
+use crate::{parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct LengthArgs {
+    /// .chart file to be used, in its song folder
+    input_file: PathBuf,
+
+    /// Audio file to compare with, instead of the streams named in [Song]
+    #[arg(short, long)]
+    audio_file: Option<PathBuf>,
+}
+
+/// Duration of an audio file, or `None` if it cannot be read or recognised.
+fn file_duration(path: &Path) -> Option<f64> {
+    audio_duration(&std::fs::read(path).ok()?)
+}
+
+pub(crate) fn run(args: &LengthArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let chart = parse_chart(&text)?;
+    let audio_length = if let Some(audio_file) = &args.audio_file {
+        file_duration(audio_file)
+    } else {
+        // every stream plays along, so the longest one sets the end
+        let folder = args.input_file.parent().unwrap_or(Path::new(""));
+        AudioStream::ALL
+            .into_iter()
+            .filter_map(|stream| chart.song().stream(stream))
+            .filter_map(|file| file_duration(&folder.join(file.as_ref())))
+            .reduce(f64::max)
+    };
+    let report = chart.check_length(audio_length);
+    eprint!("{report}");
+    if report.runs_past_audio() {
+        return Err(Error::PastAudio);
+    }
+    Ok(())
+}
@@ -1,8 +1,32 @@
 #![forbid(unsafe_code)]
 
-use chart_file_parser::chart::Chart;
-use clap::Parser;
-use std::{io::Write, path::PathBuf};
+mod audio;
+mod convert;
+mod crop;
+mod diff;
+mod info;
+mod length;
+mod medley;
+mod merge;
+mod normalize;
+mod offset;
+mod output;
+mod quantize;
+mod reduce;
+mod remap;
+mod star_power;
+mod sustains;
+mod time_edit;
+mod transplant;
+mod validate;
+
+use chart_file_parser::{
+    chart::Chart, MedleyError, OffsetError, ReduceError, RemapError, RescaleError, StarPowerError,
+    TransplantError,
+};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use output::OutputArgs;
+use std::{ffi::OsString, path::PathBuf};
 
 #[cfg(feature = "dhat-heap")]
 #[global_allocator]
@@ -10,44 +34,218 @@ static ALLOC: dhat::Alloc = dhat::Alloc;
 
 #[derive(Parser)]
 struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Multiply the resolution of a chart (used when no command is given)
+    Multiply(MultiplyArgs),
+
+    /// Show resolution, length, tempo and note counts of a chart
+    Info(info::InfoArgs),
+
+    /// Convert a chart between .chart, JSON and YAML
+    Convert(convert::ConvertArgs),
+
+    /// Compare two charts note by note, ignoring resolution changes
+    Diff(diff::DiffArgs),
+
+    /// Check a chart for problems that make it unplayable
+    Validate(validate::ValidateArgs),
+
+    /// Sort events by tick and remove exact duplicates
+    Normalize(normalize::NormalizeArgs),
+
+    /// Snap notes, sustains, star power and lyrics onto a beat grid
+    Quantize(quantize::QuantizeArgs),
+
+    /// Move the audio offset into a lead-in tempo segment so Offset can be 0
+    Offset(offset::OffsetArgs),
+
+    /// Cut a section or tick range out into a standalone practice chart
+    Crop(crop::CropArgs),
+
+    /// Join charts end to end into a medley
+    Medley(medley::MedleyArgs),
+
+    /// Copy tracks or lyrics from another chart of the same song
+    Transplant(transplant::TransplantArgs),
+
+    /// Generate Hard, Medium and Easy tracks from an Expert guitar track
+    Reduce(reduce::ReduceArgs),
+
+    /// Mirror, shift or swap the lanes of guitar and drum tracks
+    Remap(remap::RemapArgs),
+
+    /// Remove tiny sustains and trim sustains that run into the next note
+    Sustains(sustains::SustainsArgs),
+
+    /// Copy star power phrases from one difficulty to the others
+    StarPower(star_power::StarPowerArgs),
+
+    /// Check the audio files named in [Song] against the song folder
+    Audio(audio::AudioArgs),
+
+    /// Check that the chart ends before its audio does
+    Length(length::LengthArgs),
+
+    /// Merge two edited versions of a chart, keeping ours on conflicts
+    Merge(merge::MergeArgs),
+
+    /// Insert time at a tick, moving everything after it later
+    InsertTime(time_edit::TimeEditArgs),
+
+    /// Remove time starting at a tick, moving everything after it earlier
+    RemoveTime(time_edit::TimeEditArgs),
+}
+
+#[derive(Args)]
+struct MultiplyArgs {
     /// .chart file to be used
     input_file: PathBuf,
 
-    /// .chart file to be written to
-    #[arg(short, long)]
-    output_file: Option<PathBuf>,
-
     /// Factor to multiply resolution by
-    multiplier: u32,
+    #[arg(required_unless_present = "minimize")]
+    multiplier: Option<u32>,
+
+    /// Rescale down to the lowest resolution that keeps every event exact
+    #[arg(long, conflicts_with = "multiplier")]
+    minimize: bool,
+
+    /// Events this many ticks apart or closer keep their distance
+    #[arg(long, default_value_t = 1)]
+    threshold: u32,
+
+    /// Sort events and remove duplicates before multiplying
+    #[arg(long)]
+    normalize: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
 }
 
 #[derive(Debug, thiserror::Error)]
 enum Error {
     #[error(transparent)]
-    IoError(#[from] std::io::Error),
+    Io(#[from] std::io::Error),
 
     #[error("Parsing failed")]
-    ParseError(String),
+    Parse(String),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Cannot overwrite a {0:?} file with {1:?}, use --output-file instead")]
+    InPlaceConvert(convert::Format, convert::Format),
+
+    #[error("Chart has {0} errors")]
+    Invalid(usize),
+
+    #[error("Merge has {0} conflicts")]
+    Conflicts(usize),
+
+    #[error("Chart refers to {0} missing audio files")]
+    MissingAudio(usize),
+
+    #[error("Chart runs past the end of the audio")]
+    PastAudio,
+
+    #[error(transparent)]
+    Rescale(#[from] RescaleError),
+
+    #[error("No section range from {0:?} to {1:?}")]
+    Section(String, String),
+
+    #[error(transparent)]
+    Offset(#[from] OffsetError),
+
+    #[error(transparent)]
+    Medley(#[from] MedleyError),
+
+    #[error(transparent)]
+    Transplant(#[from] TransplantError),
+
+    #[error(transparent)]
+    Reduce(#[from] ReduceError),
+
+    #[error(transparent)]
+    Remap(#[from] RemapError),
+
+    #[error(transparent)]
+    StarPower(#[from] StarPowerError),
+}
+
+fn parse_chart(text: &str) -> Result<Chart<'_>, Error> {
+    Ok(Chart::parse(text)
+        .map_err(|err| Error::Parse(err.to_string()))?
+        .1)
+}
+
+fn multiply(args: &MultiplyArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    if args.normalize {
+        eprint!("{}", chart.normalize());
+    }
+    if let Some(multiplier) = args.multiplier {
+        chart.multiply_with_threshold(multiplier, args.threshold)?;
+    }
+    if args.minimize {
+        let resolution = chart.resolution();
+        eprintln!("Resolution: {resolution} -> {}", chart.minimize()?);
+    }
+    args.output.write(&args.input_file, &chart.to_string())
+}
+
+/// Command line arguments, with `multiply` inserted when no command is given
+/// so that `<INPUT_FILE> <MULTIPLIER>` keeps working.
+fn args() -> Vec<OsString> {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    let command = Cli::command();
+    let is_command = |arg: &OsString| {
+        matches!(
+            arg.to_str(),
+            Some("-h" | "--help" | "-V" | "--version" | "help")
+        ) || command
+            .get_subcommands()
+            .any(|subcommand| Some(subcommand.get_name()) == arg.to_str())
+    };
+    if args.get(1).is_some_and(|arg| !is_command(arg)) {
+        args.insert(1, "multiply".into());
+    }
+    args
 }
 
 fn main() -> Result<(), Error> {
     #[cfg(feature = "dhat-heap")]
     let _profiler = dhat::Profiler::new_heap();
 
-    let cli = Cli::parse();
-    let text = std::fs::read_to_string(cli.input_file)?;
-    let mut chart = Chart::parse(&text)
-        .map_err(|err| Error::ParseError(err.to_string()))?
-        .1;
-    chart.multiply(cli.multiplier);
-    match cli.output_file {
-        Some(file) => {
-            let mut file = std::fs::File::create(file)?;
-            file.write_all(chart.to_string().as_bytes())?;
-        }
-        None => {
-            println!("{chart}");
-        }
+    let cli = Cli::parse_from(args());
+    match cli.command {
+        Command::Multiply(args) => multiply(&args),
+        Command::Info(args) => info::run(&args),
+        Command::Convert(args) => convert::run(&args),
+        Command::Diff(args) => diff::run(&args),
+        Command::Validate(args) => validate::run(&args),
+        Command::Normalize(args) => normalize::run(&args),
+        Command::Quantize(args) => quantize::run(&args),
+        Command::Offset(args) => offset::run(&args),
+        Command::Crop(args) => crop::run(&args),
+        Command::Medley(args) => medley::run(&args),
+        Command::Transplant(args) => transplant::run(&args),
+        Command::Reduce(args) => reduce::run(&args),
+        Command::Remap(args) => remap::run(&args),
+        Command::Sustains(args) => sustains::run(&args),
+        Command::StarPower(args) => star_power::run(&args),
+        Command::Audio(args) => audio::run(&args),
+        Command::Length(args) => length::run(&args),
+        Command::Merge(args) => merge::run(&args),
+        Command::InsertTime(args) => time_edit::insert(&args),
+        Command::RemoveTime(args) => time_edit::remove(&args),
     }
-    Ok(())
 }
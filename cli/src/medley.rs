@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+
+use chart_file_parser::Chart;
+use clap::Args;
+
+use crate::{output::write_output, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct MedleyArgs {
+    /// .chart files to be joined, in order
+    #[arg(required = true, num_args = 2..)]
+    input_files: Vec<PathBuf>,
+
+    /// .chart file to be written to
+    #[arg(short, long)]
+    output_file: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: &MedleyArgs) -> Result<(), Error> {
+    let texts = args
+        .input_files
+        .iter()
+        .map(std::fs::read_to_string)
+        .collect::<Result<Vec<_>, _>>()?;
+    let charts = texts
+        .iter()
+        .map(|text| parse_chart(text))
+        .collect::<Result<Vec<_>, _>>()?;
+    let medley = Chart::concatenate(charts)?;
+    write_output(args.output_file.as_deref(), &medley.to_string(), None)
+}
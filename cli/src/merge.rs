@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use chart_file_parser::Chart;
+use clap::Args;
+
+use crate::{output::write_output, parse_chart, Error};
+
+/// Usable as a git merge driver:
+/// `driver = lyric-resolution-changer-cli merge %O %A %B -o %A`
+#[derive(Args)]
+pub(crate) struct MergeArgs {
+    /// Common ancestor .chart file
+    base: PathBuf,
+
+    /// Our edited .chart file
+    ours: PathBuf,
+
+    /// Their edited .chart file
+    theirs: PathBuf,
+
+    /// .chart file to be written to
+    #[arg(short, long)]
+    output_file: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: &MergeArgs) -> Result<(), Error> {
+    let base_text = std::fs::read_to_string(&args.base)?;
+    let ours_text = std::fs::read_to_string(&args.ours)?;
+    let theirs_text = std::fs::read_to_string(&args.theirs)?;
+    let base = parse_chart(&base_text)?;
+    let ours = parse_chart(&ours_text)?;
+    let theirs = parse_chart(&theirs_text)?;
+    let merge = Chart::merge(&base, &ours, &theirs)?;
+    for conflict in &merge.conflicts {
+        eprint!("{conflict}");
+    }
+    write_output(args.output_file.as_deref(), &merge.chart.to_string(), None)?;
+    if !merge.conflicts.is_empty() {
+        return Err(Error::Conflicts(merge.conflicts.len()));
+    }
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct NormalizeArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &NormalizeArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    eprint!("{}", chart.normalize());
+    args.output.write(&args.input_file, &chart.to_string())
+}
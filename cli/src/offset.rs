@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct OffsetArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Move events this many milliseconds later (earlier if negative) instead
+    /// of folding the [Song] Offset into the tempo map
+    #[arg(long, allow_negative_numbers = true)]
+    ms: Option<f64>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &OffsetArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    match args.ms {
+        Some(ms) => chart.shift_audio(ms)?,
+        None => eprintln!("Offset: {} ms -> 0", chart.bake_offset()?),
+    }
+    args.output.write(&args.input_file, &chart.to_string())
+}
@@ -0,0 +1,85 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+
+use crate::Error;
+
+/// Where a transformed chart gets written.
+#[derive(Args)]
+pub(crate) struct OutputArgs {
+    /// .chart file to be written to
+    #[arg(short, long, conflicts_with = "in_place")]
+    output_file: Option<PathBuf>,
+
+    /// Overwrite the input file instead of printing the result
+    #[arg(short, long)]
+    in_place: bool,
+
+    /// Keep a copy of any file that gets overwritten, with the given suffix
+    #[arg(
+        short,
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak"
+    )]
+    backup: Option<String>,
+}
+
+impl OutputArgs {
+    /// Whether the input file gets overwritten.
+    pub(crate) fn in_place(&self) -> bool {
+        self.in_place
+    }
+
+    /// Write the contents to the selected destination, or print them to stdout.
+    pub(crate) fn write(&self, input_file: &Path, contents: &str) -> Result<(), Error> {
+        let output_file = if self.in_place {
+            Some(input_file)
+        } else {
+            self.output_file.as_deref()
+        };
+        write_output(output_file, contents, self.backup.as_deref())
+    }
+}
+
+/// Write the contents to `output_file`, or print them to stdout if there is none.
+pub(crate) fn write_output(
+    output_file: Option<&Path>,
+    contents: &str,
+    backup: Option<&str>,
+) -> Result<(), Error> {
+    match output_file {
+        Some(file) => write_atomic(file, contents, backup)?,
+        None => {
+            println!("{contents}");
+        }
+    }
+    Ok(())
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it over
+/// `path`, so that readers never observe a partially written file.
+fn write_atomic(path: &Path, contents: &str, backup: Option<&str>) -> Result<(), Error> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(contents.as_bytes())?;
+    file.as_file().sync_all()?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        file.as_file().set_permissions(metadata.permissions())?;
+        if let Some(suffix) = backup {
+            let mut backup_path = path.as_os_str().to_owned();
+            backup_path.push(suffix);
+            std::fs::copy(path, backup_path)?;
+        }
+    }
+    file.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
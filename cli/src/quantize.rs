@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct QuantizeArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Grid to snap to, as parts of a whole note (16 for sixteenths, 12 for triplet eighths)
+    #[arg(short, long, default_value_t = 16)]
+    grid: u32,
+
+    /// Leave events further than this many ticks from the grid alone
+    #[arg(short, long, default_value_t = 4)]
+    tolerance: u32,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &QuantizeArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    eprint!("{}", chart.quantize(args.grid, args.tolerance));
+    args.output.write(&args.input_file, &chart.to_string())
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use chart_file_parser::ReduceOptions;
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct ReduceArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Expert track to reduce, by section name without brackets
+    #[arg(short = 'T', long = "track", default_value = "ExpertSingle")]
+    tracks: Vec<String>,
+
+    /// Closest spacing of Hard chords, as parts of a whole note
+    #[arg(long, default_value_t = ReduceOptions::default().hard.subdivision)]
+    hard_grid: u32,
+
+    /// Closest spacing of Medium chords, as parts of a whole note
+    #[arg(long, default_value_t = ReduceOptions::default().medium.subdivision)]
+    medium_grid: u32,
+
+    /// Closest spacing of Easy chords, as parts of a whole note
+    #[arg(long, default_value_t = ReduceOptions::default().easy.subdivision)]
+    easy_grid: u32,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &ReduceArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    let mut options = ReduceOptions::default();
+    options.hard.subdivision = args.hard_grid;
+    options.medium.subdivision = args.medium_grid;
+    options.easy.subdivision = args.easy_grid;
+    for name in &args.tracks {
+        eprint!("{}", chart.reduce(name, &options)?);
+    }
+    args.output.write(&args.input_file, &chart.to_string())
+}
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use chart_file_parser::LaneRemap;
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct RemapArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Five-fret or drum track to remap, by section name without brackets
+    #[arg(short = 'T', long = "track", required = true)]
+    tracks: Vec<String>,
+
+    #[command(flatten)]
+    remap: RemapChoice,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct RemapChoice {
+    /// Reverse the lanes, for left-handed players
+    #[arg(long)]
+    mirror: bool,
+
+    /// Move every note this many lanes up, or down if negative
+    #[arg(long, allow_hyphen_values = true)]
+    shift: Option<i32>,
+
+    /// Exchange two lanes, by their note numbers (2 3 swaps yellow and blue drums)
+    #[arg(long, num_args = 2)]
+    swap: Option<Vec<u32>>,
+}
+
+pub(crate) fn run(args: &RemapArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    let remap = match (args.remap.shift, args.remap.swap.as_deref()) {
+        (Some(lanes), _) => LaneRemap::Shift(lanes),
+        (None, Some(&[a, b])) => LaneRemap::Swap(a, b),
+        _ => LaneRemap::Mirror,
+    };
+    for name in &args.tracks {
+        let moved = chart.remap_lanes(name, remap)?;
+        eprintln!("[{name}]: moved {moved} notes");
+    }
+    args.output.write(&args.input_file, &chart.to_string())
+}
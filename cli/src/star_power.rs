@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct StarPowerArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Track to copy star power from, by section name without brackets
+    #[arg(short = 'T', long = "track", default_value = "ExpertSingle")]
+    tracks: Vec<String>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &StarPowerArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    for name in &args.tracks {
+        eprint!("{}", chart.copy_star_power(name)?);
+    }
+    args.output.write(&args.input_file, &chart.to_string())
+}
@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use chart_file_parser::SustainOptions;
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct SustainsArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Remove sustains shorter than this many beats
+    #[arg(short, long, default_value_t = SustainOptions::default().min_length)]
+    min_length: f64,
+
+    /// Leave this many beats between a sustain and the next note
+    #[arg(short, long, default_value_t = SustainOptions::default().gap)]
+    gap: f64,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &SustainsArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    let options = SustainOptions {
+        min_length: args.min_length,
+        gap: args.gap,
+    };
+    eprint!("{}", chart.clean_sustains(&options));
+    args.output.write(&args.input_file, &chart.to_string())
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use chart_file_parser::{Chart, Tick};
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+/// Amount of time to insert or remove, in exactly one unit.
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+pub(crate) struct Amount {
+    /// Number of ticks
+    #[arg(long)]
+    ticks: Option<u32>,
+
+    /// Number of beats, following the time signatures from the start tick
+    #[arg(long)]
+    beats: Option<u32>,
+
+    /// Number of measures, following the time signatures from the start tick
+    #[arg(long)]
+    measures: Option<u32>,
+}
+
+impl Amount {
+    fn ticks(&self, chart: &Chart, at: Tick) -> u32 {
+        let time_signatures = chart.time_signature_map();
+        let (count, length): (u32, &dyn Fn(Tick) -> f64) = match (self.beats, self.measures) {
+            (Some(beats), _) => (beats, &|tick| time_signatures.beat_length(tick)),
+            (_, Some(measures)) => (measures, &|tick| time_signatures.measure_length(tick)),
+            (None, None) => return self.ticks.unwrap_or_default(),
+        };
+        let mut end = f64::from(at);
+        for _ in 0..count {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let tick = Tick::new(end.round() as u32);
+            end += length(tick);
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let ticks = (end - f64::from(at)).round() as u32;
+        ticks
+    }
+}
+
+#[derive(Args)]
+pub(crate) struct TimeEditArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Tick at which to insert or start removing time
+    #[arg(long)]
+    at: u32,
+
+    #[command(flatten)]
+    amount: Amount,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn insert(args: &TimeEditArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    let at = Tick::new(args.at);
+    let ticks = args.amount.ticks(&chart, at);
+    chart.insert_time(at, ticks)?;
+    args.output.write(&args.input_file, &chart.to_string())
+}
+
+pub(crate) fn remove(args: &TimeEditArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let mut chart = parse_chart(&text)?;
+    let at = Tick::new(args.at);
+    let ticks = args.amount.ticks(&chart, at);
+    chart.remove_time(at..at.saturating_add(ticks));
+    args.output.write(&args.input_file, &chart.to_string())
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{output::OutputArgs, parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct TransplantArgs {
+    /// .chart file to copy into
+    input_file: PathBuf,
+
+    /// .chart file to copy from, sharing the same audio
+    source_file: PathBuf,
+
+    /// Track to copy, by section name without brackets
+    #[arg(short = 'T', long = "track", required_unless_present = "lyrics")]
+    tracks: Vec<String>,
+
+    /// Replace the lyrics and phrase markers too
+    #[arg(short, long)]
+    lyrics: bool,
+
+    /// Report events off this grid, as parts of a whole note
+    #[arg(short, long, default_value_t = 48)]
+    grid: u32,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(crate) fn run(args: &TransplantArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let source_text = std::fs::read_to_string(&args.source_file)?;
+    let mut chart = parse_chart(&text)?;
+    let source = parse_chart(&source_text)?;
+    for name in &args.tracks {
+        eprint!("{}", chart.transplant_track(&source, name, args.grid)?);
+    }
+    if args.lyrics {
+        eprint!("{}", chart.transplant_lyrics(&source, args.grid)?);
+    }
+    args.output.write(&args.input_file, &chart.to_string())
+}
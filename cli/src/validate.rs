@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use chart_file_parser::Severity;
+use clap::Args;
+
+use crate::{parse_chart, Error};
+
+#[derive(Args)]
+pub(crate) struct ValidateArgs {
+    /// .chart file to be used
+    input_file: PathBuf,
+
+    /// Print the diagnostics as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub(crate) fn run(args: &ValidateArgs) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&args.input_file)?;
+    let chart = parse_chart(&text)?;
+    let diagnostics = chart.validate();
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+    } else {
+        for diagnostic in &diagnostics {
+            print!("{diagnostic}");
+        }
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(Error::Invalid(errors));
+    }
+    Ok(())
+}
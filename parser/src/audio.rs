@@ -0,0 +1,253 @@
+use std::fmt::Display;
+
+use crate::{chart::Chart, song::AudioStream};
+
+/// File extensions of the audio formats Clone Hero plays, in order of
+/// preference when several files could replace a missing one.
+pub const AUDIO_EXTENSIONS: [&str; 4] = ["ogg", "opus", "mp3", "wav"];
+
+/// An audio stream whose file is not in the song folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MissingAudio {
+    /// Name of the `[Song]` property, such as `MusicStream`.
+    pub stream: String,
+    pub file: String,
+    /// A file in the folder with the same name up to case and extension.
+    pub replacement: Option<String>,
+}
+
+/// Summary of [`Chart::check_audio`] and [`Chart::relink_audio`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioReport {
+    pub missing: Vec<MissingAudio>,
+    /// Audio files in the folder that no stream refers to.
+    pub unreferenced: Vec<String>,
+}
+
+impl AudioReport {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unreferenced.is_empty()
+    }
+}
+
+fn split_extension(file: &str) -> (&str, &str) {
+    file.rsplit_once('.').unwrap_or((file, ""))
+}
+
+fn is_audio(file: &str) -> bool {
+    let (_, extension) = split_extension(file);
+    AUDIO_EXTENSIONS
+        .iter()
+        .any(|audio| audio.eq_ignore_ascii_case(extension))
+}
+
+/// File name of a stream's path, which may name a subfolder or be absolute.
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Name of the folder file a stream's path refers to, or `None` if the path
+/// leads into a subfolder or out of the folder.
+fn name_in_folder(path: &str) -> Option<&str> {
+    let name = path
+        .strip_prefix("./")
+        .or_else(|| path.strip_prefix(".\\"))
+        .unwrap_or(path);
+    (file_name(name) == name).then_some(name)
+}
+
+/// The file in `files` most likely meant by the missing `file`: the same name
+/// in another case, or else the same stem with the most preferred extension.
+/// Only the file name of `file` is compared, as `files` are in the folder.
+fn find_replacement<'f>(file: &str, files: &[&'f str]) -> Option<&'f str> {
+    let (stem, extension) = split_extension(file_name(file));
+    let candidates: Vec<&str> = files
+        .iter()
+        .copied()
+        .filter(|candidate| is_audio(candidate))
+        .filter(|candidate| split_extension(candidate).0.eq_ignore_ascii_case(stem))
+        .collect();
+    let rank = |candidate: &&str| {
+        let candidate_extension = split_extension(candidate).1;
+        if candidate_extension.eq_ignore_ascii_case(extension) {
+            0
+        } else {
+            1 + AUDIO_EXTENSIONS
+                .iter()
+                .position(|audio| audio.eq_ignore_ascii_case(candidate_extension))
+                .unwrap_or(AUDIO_EXTENSIONS.len())
+        }
+    };
+    candidates.into_iter().min_by_key(rank)
+}
+
+pub(crate) fn check_audio(
+    chart: &Chart,
+    files: &[&str],
+    exists: impl Fn(&str) -> bool,
+) -> AudioReport {
+    let mut report = AudioReport::default();
+    let mut referenced = vec![];
+    for stream in AudioStream::ALL {
+        let Some(file) = chart.song().stream(stream) else {
+            continue;
+        };
+        if exists(&file) {
+            referenced.extend(name_in_folder(&file).map(str::to_string));
+            continue;
+        }
+        let replacement = find_replacement(&file, files);
+        referenced.extend(replacement.map(str::to_string));
+        report.missing.push(MissingAudio {
+            stream: stream.key().to_string(),
+            file: file.into_owned(),
+            replacement: replacement.map(str::to_string),
+        });
+    }
+    report.unreferenced = files
+        .iter()
+        // file systems may ignore case, so `Song.ogg` can name `song.ogg`
+        .filter(|file| {
+            is_audio(file)
+                && !referenced
+                    .iter()
+                    .any(|other| other.eq_ignore_ascii_case(file))
+        })
+        .map(|file| (*file).to_string())
+        .collect();
+    report.unreferenced.sort();
+    report
+}
+
+pub(crate) fn relink_audio(
+    chart: &mut Chart,
+    files: &[&str],
+    exists: impl Fn(&str) -> bool,
+) -> AudioReport {
+    let report = check_audio(chart, files, exists);
+    for missing in &report.missing {
+        let stream = AudioStream::ALL
+            .into_iter()
+            .find(|stream| stream.key() == missing.stream);
+        if let (Some(stream), Some(replacement)) = (stream, &missing.replacement) {
+            chart.song_mut().set_stream(stream, replacement);
+        }
+    }
+    report
+}
+
+impl Display for AudioReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for missing in &self.missing {
+            match &missing.replacement {
+                Some(replacement) => writeln!(
+                    f,
+                    "{}: {} is missing, {} is present",
+                    missing.stream, missing.file, replacement
+                )?,
+                None => writeln!(f, "{}: {} is missing", missing.stream, missing.file)?,
+            }
+        }
+        for file in &self.unreferenced {
+            writeln!(f, "{file} is not referenced by any stream")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_audio() {
+        let mut chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+  MusicStream = \"song.ogg\"
+  GuitarStream = \"Guitar.ogg\"
+  RhythmStream = \"Rhythm.OGG\"
+  BassStream = \"bass.ogg\"
+  DrumStream = \"drums.ogg\"
+  VocalStream = \"/other/vocals.ogg\"
+  KeysStream = \"audio/keys.ogg\"
+  CrowdStream = \"C:\\Songs\\Old\\Crowd.ogg\"
+}
+[SyncTrack]
+{
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        let files = [
+            "notes.chart",
+            "song.mp3",
+            "song.opus",
+            "guitar.ogg",
+            "rhythm.ogg",
+            "drums.ogg",
+            "vocals.ogg",
+            "keys.ogg",
+            "crowd.ogg",
+            "album.png",
+        ];
+        // files next to the chart, where streams are looked up, as well as
+        // rhythm.ogg found through a case-insensitive file system
+        let exists = |path: &str| {
+            files.contains(&path)
+                || ["Rhythm.OGG", "audio/keys.ogg", "/other/vocals.ogg"].contains(&path)
+        };
+        let report = chart.relink_audio(&files, exists);
+        assert_eq!(
+            report.missing,
+            vec![
+                MissingAudio {
+                    stream: "MusicStream".to_string(),
+                    file: "song.ogg".to_string(),
+                    replacement: Some("song.opus".to_string()),
+                },
+                MissingAudio {
+                    stream: "GuitarStream".to_string(),
+                    file: "Guitar.ogg".to_string(),
+                    replacement: Some("guitar.ogg".to_string()),
+                },
+                MissingAudio {
+                    stream: "BassStream".to_string(),
+                    file: "bass.ogg".to_string(),
+                    replacement: None,
+                },
+                MissingAudio {
+                    stream: "CrowdStream".to_string(),
+                    file: "C:\\Songs\\Old\\Crowd.ogg".to_string(),
+                    replacement: Some("crowd.ogg".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            report.unreferenced,
+            vec!["keys.ogg", "song.mp3", "vocals.ogg"]
+        );
+
+        let report = chart.check_audio(&files, exists);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(
+            chart.song().stream(AudioStream::Music).unwrap(),
+            "song.opus"
+        );
+    }
+}
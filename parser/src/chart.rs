@@ -1,20 +1,53 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Range};
 
 use nom::{
     bytes::complete::take_until,
     character::complete::{multispace0, multispace1},
     combinator::all_consuming,
-    multi::separated_list1,
+    multi::separated_list0,
     IResult,
 };
 
-use crate::{events::Events, song::Song, sync_track::SyncTrack, track::Track};
+use crate::{
+    audio::{self, AudioReport},
+    crop::{self, Crop},
+    diff::{self, ChartDiff},
+    events::Events,
+    global_event::GlobalEvent,
+    length::{self, LengthReport},
+    medley::{self, MedleyError},
+    merge::{self, Merge},
+    normalize::{self, NormalizeReport},
+    offset::{self, OffsetError},
+    quantize::{self, QuantizeReport},
+    reduce::{self, ReduceError, ReduceOptions, ReduceReport},
+    remap::{self, LaneRemap, RemapError},
+    rescale::{gcd, RescaleError, TickMap},
+    song::Song,
+    star_power::{self, StarPowerError, StarPowerReport},
+    stats::ChartStats,
+    sustain::{self, SustainOptions, SustainReport},
+    sync_track::SyncTrack,
+    sync_track_event::SyncTrackEvent,
+    tempo::TempoMap,
+    tick::Tick,
+    time_edit,
+    time_signature::TimeSignatureMap,
+    track::Track,
+    track_event::TrackEvent,
+    transplant::{self, TransplantError, TransplantReport},
+    validate::{self, Diagnostic},
+};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chart<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     song: Song<'a>,
     synctrack: SyncTrack,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     global_events: Events<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     tracks: Vec<Track<'a>>,
 }
 
@@ -34,14 +67,447 @@ impl<'a> Chart<'a> {
         }
     }
 
+    /// Metadata from the `[Song]` section.
+    #[must_use]
+    pub fn song(&self) -> &Song<'a> {
+        &self.song
+    }
+
+    pub fn song_mut(&mut self) -> &mut Song<'a> {
+        &mut self.song
+    }
+
+    pub(crate) fn synctrack(&self) -> &SyncTrack {
+        &self.synctrack
+    }
+
+    pub(crate) fn global_events(&self) -> &Events<'a> {
+        &self.global_events
+    }
+
+    pub(crate) fn tracks(&self) -> &[Track<'a>] {
+        &self.tracks
+    }
+
+    pub(crate) fn synctrack_mut(&mut self) -> &mut SyncTrack {
+        &mut self.synctrack
+    }
+
+    pub(crate) fn global_events_mut(&mut self) -> &mut Events<'a> {
+        &mut self.global_events
+    }
+
+    pub(crate) fn tracks_mut(&mut self) -> &mut Vec<Track<'a>> {
+        &mut self.tracks
+    }
+
+    /// Number of ticks per beat.
+    #[must_use]
+    pub fn resolution(&self) -> u32 {
+        self.song.resolution()
+    }
+
+    /// Tick→seconds conversion for this chart's BPM markers.
+    #[must_use]
+    pub fn tempo_map(&self) -> TempoMap {
+        TempoMap::new(self.resolution(), &self.synctrack)
+    }
+
+    /// Measure and beat positions for this chart's time signatures.
+    #[must_use]
+    pub fn time_signature_map(&self) -> TimeSignatureMap {
+        TimeSignatureMap::new(self.resolution(), &self.synctrack)
+    }
+
+    /// Tick at which the last event ends, including sustains and phrase lengths.
+    #[must_use]
+    pub fn last_tick(&self) -> Tick {
+        let synctrack = self.synctrack.events().iter().map(SyncTrackEvent::time);
+        let global_events = self.global_events.events().iter().map(GlobalEvent::time);
+        let tracks = self
+            .tracks
+            .iter()
+            .flat_map(|track| track.events().iter().map(TrackEvent::end));
+        synctrack
+            .chain(global_events)
+            .chain(tracks)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Gather summary statistics about the chart.
+    #[must_use]
+    pub fn stats(&self) -> ChartStats {
+        ChartStats::new(self)
+    }
+
+    /// Check the chart for problems that make it unplayable or likely to
+    /// misbehave in game, such as unsorted events or missing tempo markers.
+    #[must_use]
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validate::validate(self)
+    }
+
+    /// Stably sort every section by tick, putting events on the same tick in
+    /// canonical order (`TS` before `B` before `A`, `N` before `S` before `E`),
+    /// and remove exact duplicates.
+    pub fn normalize(&mut self) -> NormalizeReport {
+        normalize::normalize(self)
+    }
+
+    /// Snap notes, sustain ends, `S` phrases and lyric events onto a grid that
+    /// divides a whole note into `subdivision` parts (16 for sixteenths, 12 for
+    /// eighth note triplets), restarting at every time signature change. Events
+    /// further than `tolerance` ticks from the grid are left alone. Sections
+    /// that changed are put back in canonical order, without the exact
+    /// duplicates snapping may create.
+    pub fn quantize(&mut self, subdivision: u32, tolerance: u32) -> QuantizeReport {
+        quantize::quantize(self, subdivision, tolerance)
+    }
+
+    /// Insert `ticks` ticks of silence at `at`, moving every event at or after
+    /// it later. Sustains running through `at` are cut off there, while `S`
+    /// phrases running through it are lengthened so they keep their notes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// any tick would overflow.
+    pub fn insert_time(&mut self, at: Tick, ticks: u32) -> Result<(), RescaleError> {
+        time_edit::insert_time(self, at, ticks)
+    }
+
+    /// Remove the ticks in `range`, deleting the events inside it and moving
+    /// later events earlier. Sustains and `S` phrases crossing the range are
+    /// shortened, and the last BPM and time signature inside it take effect
+    /// at its start.
+    pub fn remove_time(&mut self, range: Range<Tick>) {
+        time_edit::remove_time(self, range);
+    }
+
+    /// Move every event `delta_ms` milliseconds later in absolute time, or
+    /// earlier if negative, by rewriting the start of the tempo map. A positive
+    /// delta prepends a lead-in measure whose BPM makes it last exactly that
+    /// long. A negative delta speeds up the stretch before the first event and
+    /// restates the original BPM where it ends.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// there is not enough time before the first event to move it earlier, if
+    /// no storable BPM fits the lead-in, or if any tick would overflow.
+    pub fn shift_audio(&mut self, delta_ms: f64) -> Result<(), OffsetError> {
+        offset::shift_audio(self, delta_ms)
+    }
+
+    /// Fold the `[Song]` `Offset` into the tempo map with
+    /// [`Chart::shift_audio`] and set it to 0, returning the applied delta in
+    /// milliseconds. A positive `Offset` means tick 0 is heard that many
+    /// seconds into the audio.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// `Offset` is not a number or the shift fails.
+    pub fn bake_offset(&mut self) -> Result<f64, OffsetError> {
+        offset::bake_offset(self)
+    }
+
+    /// Copy the events in `range` into a standalone chart starting at the
+    /// measure containing `range.start`. The BPM and time signature in effect
+    /// there open the new chart, sustains are cut off at `range.end`, and
+    /// `S` phrases running through `range.start` begin there.
+    #[must_use]
+    pub fn crop(&self, range: Range<Tick>) -> Crop<'a> {
+        crop::crop(self, range)
+    }
+
+    /// Range from the section named `first` to the end of the section named
+    /// `last`, for use with [`Chart::crop`]. Returns `None` if either section
+    /// is missing or `last` comes before `first`.
+    #[must_use]
+    pub fn section_range(&self, first: &str, last: &str) -> Option<Range<Tick>> {
+        crop::section_range(self, first, last)
+    }
+
+    /// Join charts end to end into a medley at the least common multiple of
+    /// their resolutions. Each song starts on the measure after the previous
+    /// one ends, with its own BPM and time signature and a section named after
+    /// it, which replaces any section the song starts with. Tracks with the
+    /// same name are merged, and the `[Song]` properties of the first chart
+    /// are kept.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no charts are given, a chart has
+    /// resolution 0, or the common resolution or any tick would overflow.
+    pub fn concatenate(charts: Vec<Chart<'a>>) -> Result<Chart<'a>, MedleyError> {
+        medley::concatenate(charts)
+    }
+
+    /// Copy the track `name` from `source` into this chart, replacing any
+    /// track with the same name. Every event keeps its absolute time in
+    /// seconds, so the two charts may differ in resolution and tempo map as
+    /// long as they share the same audio. Events landing off this chart's
+    /// `1/subdivision` grid are reported.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// `source` has no such track or an event would land beyond the largest
+    /// representable tick.
+    pub fn transplant_track(
+        &mut self,
+        source: &Chart<'a>,
+        name: &str,
+        subdivision: u32,
+    ) -> Result<TransplantReport, TransplantError> {
+        transplant::transplant_track(self, source, name, subdivision)
+    }
+
+    /// Replace the lyrics and phrase markers of this chart with those of
+    /// `source`, retimed like [`Chart::transplant_track`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if an
+    /// event would land beyond the largest representable tick.
+    pub fn transplant_lyrics(
+        &mut self,
+        source: &Chart<'a>,
+        subdivision: u32,
+    ) -> Result<TransplantReport, TransplantError> {
+        transplant::transplant_lyrics(self, source, subdivision)
+    }
+
+    /// Compare this chart with `new` note by note, after bringing both to a
+    /// common resolution, so that a resolution change alone shows no
+    /// differences. Notes that moved less than a sixteenth on the same fret
+    /// are reported as moved.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the common resolution or any
+    /// rescaled tick would overflow.
+    pub fn diff(&self, new: &Chart<'a>) -> Result<ChartDiff, RescaleError> {
+        diff::diff(self, new)
+    }
+
+    /// Three-way merge of two charts edited independently from `base`. All
+    /// three are brought to a common resolution, then every event, `[Song]`
+    /// property and track is merged on its own, so that edits to different
+    /// notes, sections or lyrics combine cleanly. Where both sides changed
+    /// the same event differently, our version is kept and a conflict is
+    /// reported.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the common resolution or any
+    /// rescaled tick would overflow.
+    pub fn merge(
+        base: &Chart<'a>,
+        ours: &Chart<'a>,
+        theirs: &Chart<'a>,
+    ) -> Result<Merge<'a>, RescaleError> {
+        merge::merge(base, ours, theirs)
+    }
+
+    /// Generate the Hard, Medium and Easy tracks of the same instrument from
+    /// the Expert five-fret track `name`, replacing any that exist. Chords are
+    /// thinned out by beat strength, cut down to fewer and lower frets, and
+    /// lose their forced and tap modifiers, while star power phrases and
+    /// track events such as `solo` and `soloend` are copied as they are.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// `name` is not an Expert five-fret track of this chart.
+    pub fn reduce(
+        &mut self,
+        name: &str,
+        options: &ReduceOptions,
+    ) -> Result<ReduceReport, ReduceError> {
+        reduce::reduce(self, name, options)
+    }
+
+    /// Rearrange the lanes of the five-fret or drum track `name`, for
+    /// left-handed players or custom controllers. Accent, ghost and cymbal
+    /// markers follow their pads, and cymbal markers moved onto the red pad
+    /// are dropped. Returns the number of notes moved.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// `name` is not a five-fret or drum track of this chart, or a lane to
+    /// swap does not exist on it.
+    pub fn remap_lanes(&mut self, name: &str, remap: LaneRemap) -> Result<usize, RemapError> {
+        remap::remap_lanes(self, name, remap)
+    }
+
+    /// Trim every sustain to leave `options.gap` beats before the next note
+    /// on any lane, then remove sustains shorter than `options.min_length`
+    /// beats.
+    pub fn clean_sustains(&mut self, options: &SustainOptions) -> SustainReport {
+        sustain::clean_sustains(self, options)
+    }
+
+    /// Copy the star power phrases of track `name` to the other difficulties
+    /// of the same instrument, replacing theirs. Each phrase is fitted to the
+    /// notes it holds on the target track, from the first to just past the
+    /// last, and is left out where it holds none.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// `name` is not a standard track name or the chart has no such track.
+    pub fn copy_star_power(&mut self, name: &str) -> Result<StarPowerReport, StarPowerError> {
+        star_power::copy_star_power(self, name)
+    }
+
+    /// Compare the audio streams named in `[Song]` with the song folder,
+    /// reporting streams whose file is missing and audio files no stream
+    /// refers to. `files` are the names of the files in the folder, and
+    /// `exists` tells whether a stream's path, relative to the folder or
+    /// absolute, leads to a file. Only a stream naming a file directly in the
+    /// folder, in any case, accounts for that file.
+    #[must_use]
+    pub fn check_audio(&self, files: &[&str], exists: impl Fn(&str) -> bool) -> AudioReport {
+        audio::check_audio(self, files, exists)
+    }
+
+    /// Like [`Chart::check_audio`], but also point every missing stream with
+    /// a likely replacement in the folder, such as `song.opus` for
+    /// `audio/song.ogg`, at that file.
+    pub fn relink_audio(&mut self, files: &[&str], exists: impl Fn(&str) -> bool) -> AudioReport {
+        audio::relink_audio(self, files, exists)
+    }
+
+    /// Compare the end of the chart with `audio_length`, the duration in
+    /// seconds of its audio, and find the `end` event. Players may crash or
+    /// hang on charts that run past the end of the audio.
+    #[must_use]
+    pub fn check_length(&self, audio_length: Option<f64>) -> LengthReport {
+        length::check_length(self, audio_length)
+    }
+
     /// Multiply all timestamps and durations by the given factor. If two events have a 1-tick difference, this difference is preserved.
-    pub fn multiply(&mut self, factor: u32) {
-        self.song.multiply(factor);
-        self.synctrack.multiply(factor);
-        self.global_events.multiply(factor);
-        for item in &mut self.tracks {
-            item.multiply(factor);
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// any tick or the resolution would overflow.
+    pub fn multiply(&mut self, factor: u32) -> Result<(), RescaleError> {
+        self.multiply_with_threshold(factor, 1)
+    }
+
+    /// Multiply all timestamps and durations by the given factor. Events within
+    /// `threshold` ticks of the previous event, in any section, keep their
+    /// original distance to it. Sustains and phrases ending within `threshold`
+    /// ticks of an event keep their distance to that event.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// any tick or the resolution would overflow. The error identifies the
+    /// first overflowing event in file order.
+    pub fn multiply_with_threshold(
+        &mut self,
+        factor: u32,
+        threshold: u32,
+    ) -> Result<(), RescaleError> {
+        self.rescale(factor, 1, threshold)
+    }
+
+    /// Smallest resolution at which every event time and length is still a
+    /// whole number of ticks.
+    #[must_use]
+    pub fn lowest_lossless_resolution(&self) -> u32 {
+        match self.common_divisor() {
+            0 => self.resolution(),
+            divisor => self.resolution() / divisor,
+        }
+    }
+
+    /// Rescale the chart down to [`Chart::lowest_lossless_resolution`],
+    /// returning the new resolution.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, leaving the chart unchanged, if
+    /// an event's end lies beyond the largest representable tick.
+    pub fn minimize(&mut self) -> Result<u32, RescaleError> {
+        let divisor = self.common_divisor();
+        if divisor > 1 {
+            self.rescale(1, divisor, 0)?;
+        }
+        Ok(self.resolution())
+    }
+
+    /// Greatest common divisor of the resolution and every event time and length.
+    /// Rescale down to `resolution` if every event time and length stays a
+    /// whole number of ticks there, returning whether it did.
+    pub(crate) fn rescale_down_to(&mut self, resolution: u32) -> Result<bool, RescaleError> {
+        if resolution == 0 || !self.resolution().is_multiple_of(resolution) {
+            return Ok(false);
+        }
+        let divisor = self.resolution() / resolution;
+        if !self.common_divisor().is_multiple_of(divisor) {
+            return Ok(false);
+        }
+        if divisor > 1 {
+            self.rescale(1, divisor, 0)?;
         }
+        Ok(true)
+    }
+
+    fn common_divisor(&self) -> u32 {
+        let lengths = self
+            .tracks
+            .iter()
+            .flat_map(|track| track.events().iter().map(TrackEvent::length));
+        self.event_times()
+            .map(Tick::get)
+            .chain(lengths)
+            .fold(self.resolution(), gcd)
+    }
+
+    /// Multiply all timestamps, durations and the resolution by
+    /// `numerator / denominator`, keeping events within `threshold` ticks of
+    /// each other at their original distance.
+    fn rescale(
+        &mut self,
+        numerator: u32,
+        denominator: u32,
+        threshold: u32,
+    ) -> Result<(), RescaleError> {
+        let map = TickMap::new(self.event_times(), numerator, denominator, threshold);
+        let mut rescaled = self.clone();
+        rescaled.song.rescale(numerator, denominator)?;
+        rescaled
+            .synctrack
+            .rescale(&map)
+            .map_err(|time| RescaleError::event("SyncTrack", time))?;
+        rescaled
+            .global_events
+            .rescale(&map)
+            .map_err(|time| RescaleError::event("Events", time))?;
+        for item in &mut rescaled.tracks {
+            item.rescale(&map)
+                .map_err(|time| RescaleError::event(item.name(), time))?;
+        }
+        *self = rescaled;
+        Ok(())
+    }
+
+    /// Start ticks of every event in every section.
+    fn event_times(&self) -> impl Iterator<Item = Tick> + '_ {
+        let synctrack = self.synctrack.events().iter().map(SyncTrackEvent::time);
+        let global_events = self.global_events.events().iter().map(GlobalEvent::time);
+        let tracks = self
+            .tracks
+            .iter()
+            .flat_map(|track| track.events().iter().map(TrackEvent::time));
+        synctrack.chain(global_events).chain(tracks)
     }
 
     /// Parse the .chart
@@ -58,7 +524,7 @@ impl<'a> Chart<'a> {
         let (input, _) = multispace0(input)?;
         let (input, global_events) = Events::parse(input)?;
         let (input, _) = multispace0(input)?;
-        let (input, tracks) = separated_list1(multispace1, Track::parse)(input)?;
+        let (input, tracks) = separated_list0(multispace1, Track::parse)(input)?;
         let (input, _) = all_consuming(multispace0)(input)?;
         Ok((input, Chart::new(song, synctrack, global_events, tracks)))
     }
@@ -76,3 +542,69 @@ impl<'a> Display for Chart<'a> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_chart() {
+        Chart::parse(include_str!("test_data/test_chart.txt")).unwrap();
+    }
+
+    #[test]
+    fn test_multiply_keeps_adjacent_events() {
+        let mut chart = Chart::parse(include_str!("test_data/test_chart.txt"))
+            .unwrap()
+            .1;
+        chart.multiply(2).unwrap();
+        let written = chart.to_string();
+        assert!(written.contains("  1537 = E \"lyric lo\""));
+        assert!(written.contains("  1153 = N 5 0"));
+    }
+
+    #[test]
+    fn test_minimize() {
+        let mut chart = Chart::parse(include_str!("test_data/test_chart.txt"))
+            .unwrap()
+            .1;
+        assert_eq!(chart.lowest_lossless_resolution(), 192);
+        chart.multiply_with_threshold(5, 0).unwrap();
+        assert_eq!(chart.lowest_lossless_resolution(), 192);
+        let mut expected = Chart::parse(include_str!("test_data/test_chart.txt"))
+            .unwrap()
+            .1;
+        expected.multiply_with_threshold(1, 0).unwrap();
+        assert_eq!(chart.minimize(), Ok(192));
+        assert_eq!(chart, expected);
+    }
+
+    #[test]
+    fn test_multiply_overflow() {
+        let mut chart = Chart::parse(include_str!("test_data/test_chart.txt"))
+            .unwrap()
+            .1;
+        let original = chart.clone();
+        assert_eq!(
+            chart.multiply(6_000_000),
+            Err(RescaleError::Event {
+                section: "SyncTrack".to_string(),
+                time: Tick::new(768)
+            })
+        );
+        assert_eq!(chart, original);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let chart = Chart::parse(include_str!("test_data/test_chart.txt"))
+            .unwrap()
+            .1;
+        let json = serde_json::to_string(&chart).unwrap();
+        let chart2: Chart = serde_json::from_str(&json).unwrap();
+        assert_eq!(chart, chart2);
+        assert_eq!(chart.to_string(), chart2.to_string());
+    }
+}
@@ -0,0 +1,241 @@
+use std::ops::Range;
+
+use crate::{
+    chart::Chart, events::Events, global_event::GlobalEvent, sync_track::SyncTrack,
+    sync_track_event::SyncTrackEvent, tick::Tick, track::Track, track_event::TrackEvent,
+};
+
+/// A standalone chart cut out of a longer one by [`Chart::crop`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crop<'a> {
+    pub chart: Chart<'a>,
+    /// Tick of the original chart that became tick 0, the start of the measure
+    /// containing the start of the cropped range.
+    pub origin: Tick,
+    /// Seconds to cut from the start of the audio so that it lines up with the
+    /// cropped chart.
+    pub audio_start: f64,
+}
+
+pub(crate) fn crop<'a>(chart: &Chart<'a>, range: Range<Tick>) -> Crop<'a> {
+    let Range { start, end } = range;
+    let origin = chart.time_signature_map().measure_start(start);
+    let tempo = chart.tempo_map();
+    let audio_start = tempo.seconds_at(origin);
+    let rebase = |time: Tick| Tick::new(time.get() - origin.get());
+
+    // the tempo and time signature in effect at the origin start the new chart
+    let mut synctrack: Vec<SyncTrackEvent> = chart
+        .synctrack()
+        .events()
+        .iter()
+        .filter(|event| {
+            matches!(event, SyncTrackEvent::TimeSignature { .. }) && event.time() <= origin
+        })
+        .max_by_key(|event| event.time())
+        .cloned()
+        .into_iter()
+        .collect();
+    synctrack.push(SyncTrackEvent::Bpm {
+        time: origin,
+        value: tempo.bpm_at(origin),
+    });
+    synctrack.extend(
+        chart
+            .synctrack()
+            .events()
+            .iter()
+            .filter(|event| event.time() > origin && event.time() < end)
+            .cloned(),
+    );
+    for event in &mut synctrack {
+        let time = event.time_mut();
+        *time = rebase((*time).max(origin));
+        if let SyncTrackEvent::Anchor { value, .. } = event {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let microseconds = (audio_start * 1_000_000.0).round() as u32;
+            *value = value.saturating_sub(microseconds);
+        }
+    }
+
+    let global_events = chart
+        .global_events()
+        .events()
+        .iter()
+        .filter(|event| (start..end).contains(&event.time()))
+        .cloned()
+        .map(|mut event| {
+            let time = event.time_mut();
+            *time = rebase(*time);
+            event
+        })
+        .collect();
+
+    let tracks = chart
+        .tracks()
+        .iter()
+        .map(|track| {
+            let mut events: Vec<TrackEvent> = track
+                .events()
+                .iter()
+                .filter_map(|event| crop_track_event(event, start, end))
+                .map(|mut event| {
+                    match &mut event {
+                        TrackEvent::Note { time, .. }
+                        | TrackEvent::Special { time, .. }
+                        | TrackEvent::Event { time, .. } => *time = rebase(*time),
+                    }
+                    event
+                })
+                .collect();
+            // phrases running through `start` are moved onto it, among its notes
+            events.sort_by_key(|event| (event.time(), event.type_rank()));
+            Track::new(track.name().to_string(), events)
+        })
+        .filter(|track| !track.events().is_empty())
+        .collect();
+
+    Crop {
+        chart: Chart::new(
+            chart.song().clone(),
+            SyncTrack::new(synctrack),
+            Events::new(global_events),
+            tracks,
+        ),
+        origin,
+        audio_start,
+    }
+}
+
+/// The part of a track event inside `start..end`, if any. Sustains and phrases
+/// are cut off at `end`, and phrases running through `start` begin there.
+fn crop_track_event<'a>(event: &TrackEvent<'a>, start: Tick, end: Tick) -> Option<TrackEvent<'a>> {
+    let event_end = event.end().min(end);
+    let mut event = event.clone();
+    match &mut event {
+        TrackEvent::Special { time, content, .. } if *time < start && event_end > start => {
+            *time = start;
+            *content = event_end.distance(start);
+        }
+        TrackEvent::Note {
+            time,
+            sustain: length,
+            ..
+        }
+        | TrackEvent::Special {
+            time,
+            content: length,
+            ..
+        } if (start..end).contains(time) => *length = event_end.distance(*time),
+        TrackEvent::Event { time, .. } if (start..end).contains(time) => {}
+        _ => return None,
+    }
+    Some(event)
+}
+
+/// Range from the first section named `first` to the section after the first
+/// one named `last` at or after it, or to the end of the chart.
+pub(crate) fn section_range(chart: &Chart, first: &str, last: &str) -> Option<Range<Tick>> {
+    let mut sections: Vec<(Tick, &str)> = chart
+        .global_events()
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            GlobalEvent::Section { time, name } => Some((*time, name.as_ref())),
+            _ => None,
+        })
+        .collect();
+    sections.sort_by_key(|(time, _)| *time);
+    let first_index = sections.iter().position(|(_, name)| *name == first)?;
+    let last_index = first_index
+        + sections[first_index..]
+            .iter()
+            .position(|(_, name)| *name == last)?;
+    let end = match sections.get(last_index + 1) {
+        Some((time, _)) => *time,
+        None => chart.last_tick().saturating_add(1),
+    };
+    Some(sections[first_index].0..end)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const CHART: &str = "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+  600 = B 60000
+  1000 = TS 3
+  2000 = B 90000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+  900 = E \"section Verse\"
+  1576 = E \"section Chorus\"
+}
+[ExpertSingle]
+{
+  700 = N 0 400
+  800 = S 2 1000
+  900 = N 1 0
+  1500 = N 2 200
+}
+[EasySingle]
+{
+  0 = N 0 0
+}
+";
+
+    #[test]
+    fn test_crop_sections() {
+        let chart = Chart::parse(CHART).unwrap().1;
+        let range = chart.section_range("Verse", "Verse").unwrap();
+        assert_eq!(range, Tick::new(900)..Tick::new(1576));
+        let crop = chart.crop(range);
+        assert_eq!(crop.origin, Tick::new(768));
+        assert!((crop.audio_start - 2.4375).abs() < 1e-9);
+        assert_eq!(
+            crop.chart.to_string(),
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 60000
+  232 = TS 3
+}
+[Events]
+{
+  132 = E \"section Verse\"
+}
+
+[ExpertSingle]
+{
+  132 = N 1 0
+  132 = S 2 676
+  732 = N 2 76
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_crop_empty_range() {
+        let chart = Chart::parse(CHART).unwrap().1;
+        let crop = chart.crop(Tick::new(200)..Tick::new(300));
+        assert!(crop.chart.global_events().events().is_empty());
+        assert!(crop.chart.tracks().is_empty());
+        let written = crop.chart.to_string();
+        assert_eq!(Chart::parse(&written).unwrap().1, crop.chart);
+    }
+}
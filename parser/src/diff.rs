@@ -0,0 +1,716 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use crate::{
+    chart::Chart,
+    global_event::GlobalEvent,
+    rescale::{lcm, RescaleError},
+    sync_track_event::SyncTrackEvent,
+    tick::Tick,
+    time_signature::TimeSignatureMap,
+    track::Track,
+    track_event::TrackEvent,
+};
+
+/// A single musical difference found by [`Chart::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum Change {
+    TrackAdded,
+    TrackRemoved,
+    NoteAdded {
+        fret: u32,
+        sustain: u32,
+    },
+    NoteRemoved {
+        fret: u32,
+        sustain: u32,
+    },
+    /// The note was moved here from a nearby tick.
+    NoteMoved {
+        fret: u32,
+        from: Tick,
+    },
+    SustainChanged {
+        fret: u32,
+        from: u32,
+        to: u32,
+    },
+    PhraseAdded {
+        kind: u32,
+        length: u32,
+    },
+    PhraseRemoved {
+        kind: u32,
+        length: u32,
+    },
+    PhraseChanged {
+        kind: u32,
+        from: u32,
+        to: u32,
+    },
+    BpmChanged {
+        from: Option<u32>,
+        to: Option<u32>,
+    },
+    TimeSignatureChanged {
+        from: Option<(u32, u32)>,
+        to: Option<(u32, u32)>,
+    },
+    SectionAdded {
+        name: String,
+    },
+    SectionRemoved {
+        name: String,
+    },
+    SectionRenamed {
+        from: String,
+        to: String,
+    },
+    LyricAdded {
+        text: String,
+    },
+    LyricRemoved {
+        text: String,
+    },
+    LyricChanged {
+        from: String,
+        to: String,
+    },
+    EventAdded {
+        text: String,
+    },
+    EventRemoved {
+        text: String,
+    },
+}
+
+/// A change together with where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Difference {
+    /// Name of the section, without brackets.
+    pub section: String,
+    /// One-based measure number in the new chart.
+    pub measure: u32,
+    /// Tick at the compared resolution.
+    pub tick: Tick,
+    pub change: Change,
+}
+
+/// Result of [`Chart::diff`], grouped by section and ordered by tick.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartDiff {
+    /// Resolution both charts were brought to before comparing.
+    pub resolution: u32,
+    pub differences: Vec<Difference>,
+}
+
+impl ChartDiff {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Collects differences of one section.
+struct Recorder<'a> {
+    grid: &'a TimeSignatureMap,
+    section: &'a str,
+    changes: Vec<(Tick, Change)>,
+}
+
+impl Recorder<'_> {
+    fn push(&mut self, tick: Tick, change: Change) {
+        self.changes.push((tick, change));
+    }
+
+    fn finish(mut self, diff: &mut ChartDiff) {
+        self.changes.sort_by_key(|(tick, _)| *tick);
+        diff.differences
+            .extend(self.changes.into_iter().map(|(tick, change)| Difference {
+                section: self.section.to_string(),
+                measure: self.grid.measure_number(tick),
+                tick,
+                change,
+            }));
+    }
+}
+
+/// Keys under which `old` and `new` hold different values.
+fn changed<K: Ord + Clone, V: PartialEq + Clone>(
+    old: &BTreeMap<K, V>,
+    new: &BTreeMap<K, V>,
+) -> Vec<(K, Option<V>, Option<V>)> {
+    let mut keys: Vec<&K> = old.keys().chain(new.keys()).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (from, to) = (old.get(key), new.get(key));
+            (from != to).then(|| (key.clone(), from.cloned(), to.cloned()))
+        })
+        .collect()
+}
+
+/// Copies of the charts brought to their least common resolution.
+pub(crate) fn at_common_resolution<'a, const N: usize>(
+    charts: [&Chart<'a>; N],
+) -> Result<[Chart<'a>; N], RescaleError> {
+    let resolution = charts
+        .iter()
+        .try_fold(1, |common, chart| lcm(common, chart.resolution()))?;
+    let mut rescaled = charts.map(Chart::clone);
+    for chart in &mut rescaled {
+        if resolution != 0 && chart.resolution() != 0 {
+            // no adjacency threshold, so that no tick moves
+            chart.multiply_with_threshold(resolution / chart.resolution(), 0)?;
+        }
+    }
+    Ok(rescaled)
+}
+
+pub(crate) fn diff(old: &Chart, new: &Chart) -> Result<ChartDiff, RescaleError> {
+    let [old, new] = at_common_resolution([old, new])?;
+    let grid = new.time_signature_map();
+    let mut diff = ChartDiff {
+        resolution: new.resolution(),
+        differences: vec![],
+    };
+    diff_synctrack(&old, &new, &grid, &mut diff);
+    diff_events(&old, &new, &grid, &mut diff);
+    for track in new.tracks() {
+        let mut recorder = Recorder {
+            grid: &grid,
+            section: track.name(),
+            changes: vec![],
+        };
+        match old.tracks().iter().find(|old| old.name() == track.name()) {
+            Some(old_track) => diff_track(old_track, track, new.resolution(), &mut recorder),
+            None => recorder.push(Tick::ZERO, Change::TrackAdded),
+        }
+        recorder.finish(&mut diff);
+    }
+    for track in old.tracks() {
+        if !new.tracks().iter().any(|new| new.name() == track.name()) {
+            let mut recorder = Recorder {
+                grid: &grid,
+                section: track.name(),
+                changes: vec![],
+            };
+            recorder.push(Tick::ZERO, Change::TrackRemoved);
+            recorder.finish(&mut diff);
+        }
+    }
+    Ok(diff)
+}
+
+fn diff_synctrack(old: &Chart, new: &Chart, grid: &TimeSignatureMap, diff: &mut ChartDiff) {
+    let bpms = |chart: &Chart| -> BTreeMap<Tick, u32> {
+        chart
+            .synctrack()
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                SyncTrackEvent::Bpm { time, value } => Some((*time, *value)),
+                _ => None,
+            })
+            .collect()
+    };
+    let signatures = |chart: &Chart| -> BTreeMap<Tick, (u32, u32)> {
+        chart
+            .synctrack()
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                SyncTrackEvent::TimeSignature {
+                    time,
+                    value1,
+                    value2,
+                } => Some((*time, (*value1, 1 << value2.unwrap_or(2).min(31)))),
+                _ => None,
+            })
+            .collect()
+    };
+    let mut recorder = Recorder {
+        grid,
+        section: "SyncTrack",
+        changes: vec![],
+    };
+    for (tick, from, to) in changed(&bpms(old), &bpms(new)) {
+        recorder.push(tick, Change::BpmChanged { from, to });
+    }
+    for (tick, from, to) in changed(&signatures(old), &signatures(new)) {
+        recorder.push(tick, Change::TimeSignatureChanged { from, to });
+    }
+    recorder.finish(diff);
+}
+
+fn diff_events(old: &Chart, new: &Chart, grid: &TimeSignatureMap, diff: &mut ChartDiff) {
+    let sections = |chart: &Chart| {
+        by_occurrence(
+            chart
+                .global_events()
+                .events()
+                .iter()
+                .filter_map(|event| match event {
+                    GlobalEvent::Section { time, name } => Some((*time, name.to_string())),
+                    _ => None,
+                }),
+        )
+    };
+    let lyrics = |chart: &Chart| {
+        by_occurrence(
+            chart
+                .global_events()
+                .events()
+                .iter()
+                .filter_map(|event| match event {
+                    GlobalEvent::Lyric { time, text } => Some((*time, text.to_string())),
+                    _ => None,
+                }),
+        )
+    };
+    let others = |chart: &Chart| -> BTreeSet<(Tick, String)> {
+        chart
+            .global_events()
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                GlobalEvent::Section { .. } | GlobalEvent::Lyric { .. } => None,
+                event => Some((event.time(), event.text().into_owned())),
+            })
+            .collect()
+    };
+    let mut recorder = Recorder {
+        grid,
+        section: "Events",
+        changes: vec![],
+    };
+    for ((tick, _), from, to) in changed(&sections(old), &sections(new)) {
+        let change = match (from, to) {
+            (Some(from), Some(to)) => Change::SectionRenamed { from, to },
+            (Some(name), None) => Change::SectionRemoved { name },
+            (None, Some(name)) => Change::SectionAdded { name },
+            (None, None) => continue,
+        };
+        recorder.push(tick, change);
+    }
+    for ((tick, _), from, to) in changed(&lyrics(old), &lyrics(new)) {
+        let change = match (from, to) {
+            (Some(from), Some(to)) => Change::LyricChanged { from, to },
+            (Some(text), None) => Change::LyricRemoved { text },
+            (None, Some(text)) => Change::LyricAdded { text },
+            (None, None) => continue,
+        };
+        recorder.push(tick, change);
+    }
+    diff_texts(&others(old), &others(new), &mut recorder);
+    recorder.finish(diff);
+}
+
+/// Texts keyed by tick and by their position among the texts at that tick,
+/// so that several sections or lyrics at one tick are compared one by one.
+fn by_occurrence(texts: impl Iterator<Item = (Tick, String)>) -> BTreeMap<(Tick, usize), String> {
+    let mut keyed = BTreeMap::new();
+    let mut counts: BTreeMap<Tick, usize> = BTreeMap::new();
+    for (tick, text) in texts {
+        let count = counts.entry(tick).or_default();
+        keyed.insert((tick, *count), text);
+        *count += 1;
+    }
+    keyed
+}
+
+/// Record text events present in only one of `old` and `new`.
+fn diff_texts(
+    old: &BTreeSet<(Tick, String)>,
+    new: &BTreeSet<(Tick, String)>,
+    recorder: &mut Recorder,
+) {
+    for (tick, text) in old.difference(new) {
+        recorder.push(*tick, Change::EventRemoved { text: text.clone() });
+    }
+    for (tick, text) in new.difference(old) {
+        recorder.push(*tick, Change::EventAdded { text: text.clone() });
+    }
+}
+
+fn diff_track(old: &Track, new: &Track, resolution: u32, recorder: &mut Recorder) {
+    let phrases = |track: &Track| -> BTreeMap<(Tick, u32), u32> {
+        track
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                TrackEvent::Special {
+                    time,
+                    kind,
+                    content,
+                } => Some(((*time, *kind), *content)),
+                _ => None,
+            })
+            .collect()
+    };
+    let events = |track: &Track| -> BTreeSet<(Tick, String)> {
+        track
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                TrackEvent::Event { time, value } => Some((*time, value.to_string())),
+                _ => None,
+            })
+            .collect()
+    };
+    diff_notes(old, new, resolution, recorder);
+    for ((tick, kind), from, to) in changed(&phrases(old), &phrases(new)) {
+        let change = match (from, to) {
+            (Some(from), Some(to)) => Change::PhraseChanged { kind, from, to },
+            (Some(length), None) => Change::PhraseRemoved { kind, length },
+            (None, Some(length)) => Change::PhraseAdded { kind, length },
+            (None, None) => continue,
+        };
+        recorder.push(tick, change);
+    }
+    diff_texts(&events(old), &events(new), recorder);
+}
+
+fn diff_notes(old: &Track, new: &Track, resolution: u32, recorder: &mut Recorder) {
+    let notes = |track: &Track| -> BTreeMap<(Tick, u32), u32> {
+        track
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                TrackEvent::Note {
+                    time,
+                    fret,
+                    sustain,
+                } => Some(((*time, *fret), *sustain)),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let mut added = vec![];
+    let mut removed = vec![];
+    for ((tick, fret), from, to) in changed(&notes(old), &notes(new)) {
+        match (from, to) {
+            (Some(from), Some(to)) => {
+                recorder.push(tick, Change::SustainChanged { fret, from, to });
+            }
+            (Some(sustain), None) => removed.push((tick, fret, sustain)),
+            (None, Some(sustain)) => added.push((tick, fret, sustain)),
+            (None, None) => {}
+        }
+    }
+    // a note that disappeared within a sixteenth of a new one on the same
+    // fret was moved rather than replaced
+    let window = resolution / 4;
+    let mut matched = vec![false; added.len()];
+    for (from, fret, old_sustain) in removed {
+        let nearest = added
+            .iter()
+            .enumerate()
+            .filter(|(index, (tick, new_fret, _))| {
+                !matched[*index] && *new_fret == fret && tick.distance(from) <= window
+            })
+            .min_by_key(|(_, (tick, _, _))| tick.distance(from))
+            .map(|(index, _)| index);
+        match nearest {
+            Some(index) => {
+                matched[index] = true;
+                let (tick, _, new_sustain) = added[index];
+                recorder.push(tick, Change::NoteMoved { fret, from });
+                if new_sustain != old_sustain {
+                    recorder.push(
+                        tick,
+                        Change::SustainChanged {
+                            fret,
+                            from: old_sustain,
+                            to: new_sustain,
+                        },
+                    );
+                }
+            }
+            None => recorder.push(
+                from,
+                Change::NoteRemoved {
+                    fret,
+                    sustain: old_sustain,
+                },
+            ),
+        }
+    }
+    for ((tick, fret, sustain), matched) in added.into_iter().zip(matched) {
+        if !matched {
+            recorder.push(tick, Change::NoteAdded { fret, sustain });
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let signature = |signature: &Option<(u32, u32)>| match signature {
+            Some((numerator, denominator)) => format!("{numerator}/{denominator}"),
+            None => "none".to_string(),
+        };
+        let bpm = |bpm: &Option<u32>| match bpm {
+            Some(bpm) => format!("{}", f64::from(*bpm) / 1000.0),
+            None => "none".to_string(),
+        };
+        match self {
+            Change::TrackAdded => write!(f, "track added"),
+            Change::TrackRemoved => write!(f, "track removed"),
+            Change::NoteAdded { fret, sustain } => write!(f, "+ note {fret} (sustain {sustain})"),
+            Change::NoteRemoved { fret, sustain } => {
+                write!(f, "- note {fret} (sustain {sustain})")
+            }
+            Change::NoteMoved { fret, from } => write!(f, "~ note {fret} moved from tick {from}"),
+            Change::SustainChanged { fret, from, to } => {
+                write!(f, "~ note {fret} sustain {from} -> {to}")
+            }
+            Change::PhraseAdded { kind, length } => write!(f, "+ phrase {kind} (length {length})"),
+            Change::PhraseRemoved { kind, length } => {
+                write!(f, "- phrase {kind} (length {length})")
+            }
+            Change::PhraseChanged { kind, from, to } => {
+                write!(f, "~ phrase {kind} length {from} -> {to}")
+            }
+            Change::BpmChanged { from, to } => write!(f, "~ BPM {} -> {}", bpm(from), bpm(to)),
+            Change::TimeSignatureChanged { from, to } => write!(
+                f,
+                "~ time signature {} -> {}",
+                signature(from),
+                signature(to)
+            ),
+            Change::SectionAdded { name } => write!(f, "+ section {name:?}"),
+            Change::SectionRemoved { name } => write!(f, "- section {name:?}"),
+            Change::SectionRenamed { from, to } => write!(f, "~ section {from:?} -> {to:?}"),
+            Change::LyricAdded { text } => write!(f, "+ lyric {text:?}"),
+            Change::LyricRemoved { text } => write!(f, "- lyric {text:?}"),
+            Change::LyricChanged { from, to } => write!(f, "~ lyric {from:?} -> {to:?}"),
+            Change::EventAdded { text } => write!(f, "+ event {text:?}"),
+            Change::EventRemoved { text } => write!(f, "- event {text:?}"),
+        }
+    }
+}
+
+impl Display for ChartDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut previous: Option<(&str, u32)> = None;
+        for difference in &self.differences {
+            let section = difference.section.as_str();
+            if previous.map(|(section, _)| section) != Some(section) {
+                writeln!(f, "[{section}]")?;
+                previous = None;
+            }
+            if previous.map(|(_, measure)| measure) != Some(difference.measure) {
+                writeln!(f, "  measure {}", difference.measure)?;
+            }
+            writeln!(f, "    {}: {}", difference.tick, difference.change)?;
+            previous = Some((section, difference.measure));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const OLD: &str = "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+  768 = E \"lyric Hel-\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  192 = N 1 96
+  384 = N 2 0
+  768 = N 3 0
+  768 = S 2 192
+}
+[EasySingle]
+{
+  0 = N 0 0
+}
+";
+
+    const NEW: &str = "[Song]
+{
+  Resolution = 480
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+  1920 = B 140000
+}
+[Events]
+{
+  0 = E \"section Opening\"
+  1920 = E \"lyric Hel\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  480 = N 1 480
+  1000 = N 2 0
+  1920 = N 4 0
+  1920 = S 2 480
+}
+";
+
+    /// Measure, tick and change of the differences in the given sections.
+    fn changes(sections: &[&str]) -> Vec<(u32, u32, Change)> {
+        let old = Chart::parse(OLD).unwrap().1;
+        let new = Chart::parse(NEW).unwrap().1;
+        let diff = old.diff(&new).unwrap();
+        assert_eq!(diff.resolution, 960);
+        diff.differences
+            .into_iter()
+            .filter(|difference| sections.contains(&difference.section.as_str()))
+            .map(|difference| (difference.measure, difference.tick.get(), difference.change))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_sync_track() {
+        assert_eq!(
+            changes(&["SyncTrack"]),
+            vec![(
+                2,
+                3840,
+                Change::BpmChanged {
+                    from: None,
+                    to: Some(140_000)
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diff_events() {
+        assert_eq!(
+            changes(&["Events"]),
+            vec![
+                (
+                    1,
+                    0,
+                    Change::SectionRenamed {
+                        from: "Intro".to_string(),
+                        to: "Opening".to_string()
+                    }
+                ),
+                (
+                    2,
+                    3840,
+                    Change::LyricChanged {
+                        from: "Hel-".to_string(),
+                        to: "Hel".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_tracks() {
+        assert_eq!(
+            changes(&["ExpertSingle", "EasySingle"]),
+            vec![
+                (
+                    1,
+                    960,
+                    Change::SustainChanged {
+                        fret: 1,
+                        from: 480,
+                        to: 960
+                    }
+                ),
+                (
+                    1,
+                    2000,
+                    Change::NoteMoved {
+                        fret: 2,
+                        from: Tick::new(1920)
+                    }
+                ),
+                (
+                    2,
+                    3840,
+                    Change::NoteRemoved {
+                        fret: 3,
+                        sustain: 0
+                    }
+                ),
+                (
+                    2,
+                    3840,
+                    Change::NoteAdded {
+                        fret: 4,
+                        sustain: 0
+                    }
+                ),
+                (1, 0, Change::TrackRemoved),
+            ]
+        );
+        let old = Chart::parse(OLD).unwrap().1;
+        assert!(old.diff(&old).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_exact_rescale() {
+        // notes one tick apart are what a threshold would pull together
+        let text = OLD.replace("384 = N 2 0", "384 = N 2 0\n  385 = N 3 0");
+        let old = Chart::parse(&text).unwrap().1;
+        let mut new = old.clone();
+        new.multiply_with_threshold(2, 0).unwrap();
+        assert!(old.diff(&new).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_same_tick() {
+        let old = Chart::parse(OLD).unwrap().1;
+        let new_text = OLD.replace(
+            "  0 = E \"section Intro\"\n",
+            "  0 = E \"section Intro\"\n  0 = E \"section Riff\"\n",
+        );
+        let new = Chart::parse(&new_text).unwrap().1;
+        let renamed = new_text.replace("section Riff", "section Riff 2");
+        let renamed = Chart::parse(&renamed).unwrap().1;
+        let changes = |diff: ChartDiff| -> Vec<Change> {
+            diff.differences
+                .into_iter()
+                .map(|difference| difference.change)
+                .collect()
+        };
+        assert_eq!(
+            changes(old.diff(&new).unwrap()),
+            vec![Change::SectionAdded {
+                name: "Riff".to_string()
+            }]
+        );
+        assert_eq!(
+            changes(new.diff(&renamed).unwrap()),
+            vec![Change::SectionRenamed {
+                from: "Riff".to_string(),
+                to: "Riff 2".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+/// MPEG audio bitrates in kbit/s, by version family, layer and index.
+const MPEG1_BITRATES: [[u32; 15]; 3] = [
+    [
+        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+];
+const MPEG2_BITRATES: [[u32; 15]; 2] = [
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+fn bytes<const N: usize>(data: &[u8], at: usize) -> Option<[u8; N]> {
+    data.get(at..at.checked_add(N)?)?.try_into().ok()
+}
+
+fn u16_le(data: &[u8], at: usize) -> Option<u16> {
+    bytes(data, at).map(u16::from_le_bytes)
+}
+
+fn u32_le(data: &[u8], at: usize) -> Option<u32> {
+    bytes(data, at).map(u32::from_le_bytes)
+}
+
+fn u32_be(data: &[u8], at: usize) -> Option<u32> {
+    bytes(data, at).map(u32::from_be_bytes)
+}
+
+/// Duration in seconds of a WAV, Ogg Vorbis, Opus or MP3 file, read from its
+/// headers without decoding any audio, or `None` if the format is not
+/// recognised or the headers are damaged.
+#[must_use]
+pub fn audio_duration(data: &[u8]) -> Option<f64> {
+    if data.starts_with(b"RIFF") {
+        wav_duration(data)
+    } else if data.starts_with(b"OggS") {
+        ogg_duration(data)
+    } else {
+        mp3_duration(data)
+    }
+}
+
+/// Size of the `data` chunk divided by the byte rate from the `fmt ` chunk.
+fn wav_duration(data: &[u8]) -> Option<f64> {
+    if data.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let mut position = 12;
+    let mut byte_rate = None;
+    while let Some(size) = u32_le(data, position + 4) {
+        let body = position + 8;
+        match data.get(position..position + 4)? {
+            b"fmt " => byte_rate = u32_le(data, body + 8).filter(|rate| *rate > 0),
+            b"data" => return Some(f64::from(size) / f64::from(byte_rate?)),
+            _ => {}
+        }
+        // chunks are padded to an even length
+        position = body.checked_add(usize::try_from(size).ok()? + (size & 1) as usize)?;
+    }
+    None
+}
+
+/// Granule position of the last page, which counts samples, divided by the
+/// sample rate from the identification header.
+fn ogg_duration(data: &[u8]) -> Option<f64> {
+    let segments = usize::from(*data.get(26)?);
+    let packet = data.get(27 + segments..)?;
+    let (rate, pre_skip) = if packet.starts_with(b"\x01vorbis") {
+        (u32_le(packet, 12)?, 0)
+    } else if packet.starts_with(b"OpusHead") {
+        // Opus granules always count at 48 kHz
+        (48_000, u16_le(packet, 10)?)
+    } else {
+        return None;
+    };
+    if rate == 0 {
+        return None;
+    }
+    // a page that completes no packet has a granule position of -1
+    let granule = (0..data.len().saturating_sub(3))
+        .rev()
+        .filter(|position| data[*position..].starts_with(b"OggS"))
+        .filter_map(|position| bytes(data, position + 6).map(i64::from_le_bytes))
+        .find(|granule| *granule >= 0)?;
+    #[allow(clippy::cast_precision_loss)]
+    let samples = (granule - i64::from(pre_skip)).max(0) as f64;
+    Some(samples / f64::from(rate))
+}
+
+/// A decoded MPEG audio frame header.
+struct FrameHeader {
+    /// Bits per second.
+    bitrate: u32,
+    sample_rate: u32,
+    samples_per_frame: u32,
+    /// Offset of a Xing or Info tag from the start of the frame.
+    xing_offset: usize,
+}
+
+impl FrameHeader {
+    fn parse(header: [u8; 4]) -> Option<Self> {
+        if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+            return None;
+        }
+        let version = (header[1] >> 3) & 3;
+        let layer = match (header[1] >> 1) & 3 {
+            0 => return None,
+            layer => 4 - usize::from(layer),
+        };
+        let bitrate_index = usize::from(header[2] >> 4);
+        let sample_rate_index = usize::from((header[2] >> 2) & 3);
+        if bitrate_index == 0 || bitrate_index == 15 || sample_rate_index == 3 {
+            return None;
+        }
+        let mono = header[3] >> 6 == 3;
+        let mpeg1 = version == 3;
+        let kbits = match (mpeg1, layer) {
+            (true, _) => MPEG1_BITRATES[layer - 1][bitrate_index],
+            (false, 1) => MPEG2_BITRATES[0][bitrate_index],
+            (false, _) => MPEG2_BITRATES[1][bitrate_index],
+        };
+        let sample_rate = match version {
+            3 => [44_100, 48_000, 32_000][sample_rate_index],
+            2 => [22_050, 24_000, 16_000][sample_rate_index],
+            0 => [11_025, 12_000, 8_000][sample_rate_index],
+            _ => return None,
+        };
+        let samples_per_frame = match (layer, mpeg1) {
+            (1, _) => 384,
+            (3, false) => 576,
+            _ => 1152,
+        };
+        let side_info = match (mpeg1, mono) {
+            (true, false) => 32,
+            (true, true) | (false, false) => 17,
+            (false, true) => 9,
+        };
+        Some(Self {
+            bitrate: kbits * 1000,
+            sample_rate,
+            samples_per_frame,
+            xing_offset: 4 + side_info,
+        })
+    }
+}
+
+/// Frame count from a Xing, Info or VBRI tag, or else the size of the audio
+/// divided by the bitrate of the first frame.
+fn mp3_duration(data: &[u8]) -> Option<f64> {
+    let mut start = 0;
+    if data.starts_with(b"ID3") {
+        let size = bytes::<4>(data, 6)?
+            .iter()
+            .fold(0, |size, byte| (size << 7) | usize::from(byte & 0x7F));
+        let footer = if data.get(5)? & 0x10 == 0 { 0 } else { 10 };
+        start = 10 + size + footer;
+    }
+    let (position, frame) = (start..data.len())
+        .find_map(|position| Some((position, FrameHeader::parse(bytes(data, position)?)?)))?;
+
+    let tag = |at: usize| data.get(at..at + 4).unwrap_or_default();
+    let xing = position + frame.xing_offset;
+    let frames = match tag(xing) {
+        b"Xing" | b"Info" if u32_be(data, xing + 4).is_some_and(|flags| flags & 1 == 1) => {
+            u32_be(data, xing + 8)
+        }
+        _ if tag(position + 36) == b"VBRI" => u32_be(data, position + 50),
+        _ => None,
+    };
+    if let Some(frames) = frames {
+        return Some(
+            f64::from(frames) * f64::from(frame.samples_per_frame) / f64::from(frame.sample_rate),
+        );
+    }
+    let mut end = data.len();
+    if end >= position + 128 && data[end - 128..].starts_with(b"TAG") {
+        end -= 128;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let bits = ((end - position) * 8) as f64;
+    Some(bits / f64::from(frame.bitrate))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn assert_close(duration: Option<f64>, expected: f64) {
+        assert!((duration.unwrap() - expected).abs() < 1e-6, "{duration:?}");
+    }
+
+    fn ogg_page(granule: i64, packet: &[u8]) -> Vec<u8> {
+        let mut page = b"OggS\0\0".to_vec();
+        page.extend(granule.to_le_bytes());
+        page.extend([0; 12]);
+        page.push(1);
+        page.push(u8::try_from(packet.len()).unwrap());
+        page.extend(packet);
+        page
+    }
+
+    #[test]
+    fn test_audio_duration() {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend(b"fmt \x10\0\0\0\x01\0\x02\0\x44\xac\0\0");
+        wav.extend(176_400_u32.to_le_bytes());
+        wav.extend(b"\x04\0\x10\0");
+        wav.extend(b"data");
+        wav.extend(441_000_u32.to_le_bytes());
+        assert_close(audio_duration(&wav), 2.5);
+
+        let mut opus = ogg_page(0, b"OpusHead\x01\x02\x38\x01\x80\xbb\0\0\0\0\0");
+        opus.extend(ogg_page(0, b"OpusTags"));
+        opus.extend(ogg_page(480_312, b"audio"));
+        opus.extend(ogg_page(-1, b"more"));
+        assert_close(audio_duration(&opus), 10.0);
+
+        let mut vorbis = b"\x01vorbis\0\0\0\0\x02".to_vec();
+        vorbis.extend(44_100_u32.to_le_bytes());
+        let mut vorbis = ogg_page(0, &vorbis);
+        vorbis.extend(ogg_page(132_300, b"audio"));
+        assert_close(audio_duration(&vorbis), 3.0);
+
+        // MPEG-1 layer III, 128 kbit/s, 44.1 kHz, stereo, with a Xing tag
+        let mut mp3 = b"ID3\x04\0\0\0\0\0\x02\0\0".to_vec();
+        mp3.extend([0xFF, 0xFB, 0x90, 0x00]);
+        mp3.extend([0; 32]);
+        mp3.extend(b"Xing\0\0\0\x01");
+        mp3.extend(1000_u32.to_be_bytes());
+        assert_close(audio_duration(&mp3), 1000.0 * 1152.0 / 44_100.0);
+
+        // the same frames without a tag, read as constant bitrate
+        let mut cbr = [0xFF, 0xFB, 0x90, 0x00].to_vec();
+        cbr.resize(32_000, 0);
+        assert_close(audio_duration(&cbr), 2.0);
+
+        assert_eq!(audio_duration(b"not audio"), None);
+    }
+}
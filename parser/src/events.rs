@@ -1,14 +1,18 @@
 use std::fmt::Display;
 
-use nom::{bytes::complete::tag, combinator::map, multi::many1, sequence::preceded, IResult};
+use nom::{bytes::complete::tag, combinator::map, multi::many0, sequence::preceded, IResult};
 
 use crate::{
     components::{curlied, spaced},
     global_event::GlobalEvent,
+    rescale::TickMap,
+    tick::Tick,
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Events<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     events: Vec<GlobalEvent<'a>>,
 }
 
@@ -18,24 +22,20 @@ impl<'a> Events<'a> {
         Self { events }
     }
 
-    pub(crate) fn multiply(&mut self, factor: u32) {
-        if let Some((head, tail)) = self.events.split_first_mut() {
-            let mut prev_time = head.time();
-            let mut prev_time_final = prev_time * factor;
-            head.multiply(factor);
-            for item in tail {
-                let time = item.time();
-                if time == prev_time {
-                    *item.time_mut() = prev_time_final;
-                } else if time == prev_time + 1 {
-                    *item.time_mut() = prev_time_final + 1;
-                } else {
-                    item.multiply(factor);
-                }
-                prev_time = time;
-                prev_time_final = item.time();
-            }
+    pub(crate) fn events(&self) -> &[GlobalEvent<'a>] {
+        &self.events
+    }
+
+    pub(crate) fn events_mut(&mut self) -> &mut Vec<GlobalEvent<'a>> {
+        &mut self.events
+    }
+
+    /// Rescale every event, returning the original time of the first one that overflows.
+    pub(crate) fn rescale(&mut self, map: &TickMap) -> Result<(), Tick> {
+        for item in &mut self.events {
+            item.rescale(map)?;
         }
+        Ok(())
     }
 
     #[inline]
@@ -43,7 +43,8 @@ impl<'a> Events<'a> {
         map(
             preceded(
                 spaced(tag("[Events]")),
-                curlied(many1(spaced(GlobalEvent::parse))),
+                // a cropped chart may have no events left
+                curlied(spaced(many0(spaced(GlobalEvent::parse)))),
             ),
             Self::new,
         )(input)
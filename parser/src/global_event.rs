@@ -1,71 +1,185 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display, ops::Range};
 
-use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_until},
-    combinator::{cut, map},
-    sequence::{delimited, preceded},
-    IResult,
-};
+use nom::{bytes::complete::tag, character::complete::not_line_ending, combinator::map, IResult};
 
-#[derive(Debug, PartialEq, Eq)]
+use crate::{rescale::TickMap, song_property::unquote, tick::Tick};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GlobalEvent<'a> {
-    PhraseStart { time: u32 },
-    PhraseEnd { time: u32 },
-    Section { time: u32, name: &'a str },
-    Lyric { time: u32, text: &'a str },
-    Other { time: u32, value: &'a str },
+    PhraseStart {
+        time: Tick,
+    },
+    PhraseEnd {
+        time: Tick,
+    },
+    Section {
+        time: Tick,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        name: Cow<'a, str>,
+    },
+    Lyric {
+        time: Tick,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        text: Cow<'a, str>,
+    },
+    /// Where the band starts playing, `music_start`.
+    MusicStart {
+        time: Tick,
+    },
+    /// End of the song, `end`.
+    End {
+        time: Tick,
+    },
+    /// Start of the coda, `coda`.
+    Coda {
+        time: Tick,
+    },
+    /// Start of a big rock ending, `big_rock_ending`.
+    BigRockEnding {
+        time: Tick,
+    },
+    /// A solo starting in the venue, `solo`.
+    Solo {
+        time: Tick,
+    },
+    /// A crowd cue such as `crowd_lighters_fast`, without the `crowd_` prefix.
+    Crowd {
+        time: Tick,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        kind: Cow<'a, str>,
+    },
+    /// A lighting cue such as `lighting (strobe)`, with the name between the
+    /// parentheses. The default lighting is written with an empty name.
+    Lighting {
+        time: Tick,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        kind: Cow<'a, str>,
+    },
+    Other {
+        time: Tick,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        value: Cow<'a, str>,
+    },
+}
+
+/// Part of `text`, borrowed from the chart when `text` is.
+fn slice<'a>(text: &Cow<'a, str>, range: Range<usize>) -> Cow<'a, str> {
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(&text[range]),
+        Cow::Owned(text) => Cow::Owned(text[range].to_string()),
+    }
 }
 
 impl<'a> GlobalEvent<'a> {
-    pub(crate) fn multiply(&mut self, factor: u32) {
-        match self {
-            GlobalEvent::PhraseStart { time }
-            | GlobalEvent::PhraseEnd { time }
-            | GlobalEvent::Section { time, .. }
-            | GlobalEvent::Lyric { time, .. }
-            | GlobalEvent::Other { time, .. } => *time *= factor,
-        }
+    pub(crate) fn rescale(&mut self, map: &TickMap) -> Result<(), Tick> {
+        let time = self.time_mut();
+        *time = map.time(*time).ok_or(*time)?;
+        Ok(())
     }
 
+    /// Parse an event line. The text runs to the end of the line; when it is
+    /// wrapped in quotes they are removed, and unquoted text such as
+    /// `0 = E end` is taken as it is. Like Moonscraper, this crate neither
+    /// escapes nor unescapes anything inside the quotes, so the text is read
+    /// and written back verbatim.
     #[inline]
     pub(crate) fn parse(input: &str) -> IResult<&str, GlobalEvent> {
-        let (input, time) = nom::character::complete::u32(input)?;
+        let (input, time) = map(nom::character::complete::u32, Tick::new)(input)?;
         let (input, _) = tag(" = E ")(input)?;
-        let (input, result) = delimited(
-            tag("\""),
-            alt((
-                map(tag("phrase_start"), |_| GlobalEvent::PhraseStart { time }),
-                map(tag("phrase_end"), |_| GlobalEvent::PhraseEnd { time }),
-                map(preceded(tag("section "), cut(take_until("\""))), |name| {
-                    GlobalEvent::Section { time, name }
-                }),
-                map(preceded(tag("lyric "), cut(take_until("\""))), |text| {
-                    GlobalEvent::Lyric { time, text }
-                }),
-                map(take_until("\""), |value| GlobalEvent::Other { time, value }),
-            )),
-            tag("\""),
-        )(input)?;
-        Ok((input, result))
+        let (input, text) = not_line_ending(input)?;
+        Ok((
+            input,
+            GlobalEvent::from_text(time, Cow::Borrowed(unquote(text.trim_end()))),
+        ))
+    }
+
+    /// The event written as `text` between the quotes.
+    fn from_text(time: Tick, text: Cow<'_, str>) -> GlobalEvent<'_> {
+        match text.as_ref() {
+            "phrase_start" => return GlobalEvent::PhraseStart { time },
+            "phrase_end" => return GlobalEvent::PhraseEnd { time },
+            "music_start" => return GlobalEvent::MusicStart { time },
+            "end" => return GlobalEvent::End { time },
+            "coda" => return GlobalEvent::Coda { time },
+            "big_rock_ending" => return GlobalEvent::BigRockEnding { time },
+            "solo" => return GlobalEvent::Solo { time },
+            _ => {}
+        }
+        let end = text.len();
+        if text.starts_with("section ") {
+            GlobalEvent::Section {
+                time,
+                name: slice(&text, "section ".len()..end),
+            }
+        } else if text.starts_with("lyric ") {
+            GlobalEvent::Lyric {
+                time,
+                text: slice(&text, "lyric ".len()..end),
+            }
+        } else if text.starts_with("crowd_") {
+            GlobalEvent::Crowd {
+                time,
+                kind: slice(&text, "crowd_".len()..end),
+            }
+        } else if text.starts_with("lighting (") && text.ends_with(')') {
+            GlobalEvent::Lighting {
+                time,
+                kind: slice(&text, "lighting (".len()..end - 1),
+            }
+        } else {
+            GlobalEvent::Other { time, value: text }
+        }
     }
 
-    pub(crate) fn time(&self) -> u32 {
+    /// The text written between the quotes.
+    pub(crate) fn text(&self) -> Cow<'_, str> {
+        match self {
+            GlobalEvent::PhraseStart { .. } => "phrase_start".into(),
+            GlobalEvent::PhraseEnd { .. } => "phrase_end".into(),
+            GlobalEvent::MusicStart { .. } => "music_start".into(),
+            GlobalEvent::End { .. } => "end".into(),
+            GlobalEvent::Coda { .. } => "coda".into(),
+            GlobalEvent::BigRockEnding { .. } => "big_rock_ending".into(),
+            GlobalEvent::Solo { .. } => "solo".into(),
+            GlobalEvent::Section { name, .. } => format!("section {name}").into(),
+            GlobalEvent::Lyric { text, .. } => format!("lyric {text}").into(),
+            GlobalEvent::Crowd { kind, .. } => format!("crowd_{kind}").into(),
+            GlobalEvent::Lighting { kind, .. } => format!("lighting ({kind})").into(),
+            GlobalEvent::Other { value, .. } => value.as_ref().into(),
+        }
+    }
+
+    pub(crate) fn time(&self) -> Tick {
         match self {
             GlobalEvent::PhraseStart { time }
             | GlobalEvent::PhraseEnd { time }
+            | GlobalEvent::MusicStart { time }
+            | GlobalEvent::End { time }
+            | GlobalEvent::Coda { time }
+            | GlobalEvent::BigRockEnding { time }
+            | GlobalEvent::Solo { time }
             | GlobalEvent::Section { time, .. }
             | GlobalEvent::Lyric { time, .. }
+            | GlobalEvent::Crowd { time, .. }
+            | GlobalEvent::Lighting { time, .. }
             | GlobalEvent::Other { time, .. } => *time,
         }
     }
 
-    pub(crate) fn time_mut(&mut self) -> &mut u32 {
+    pub(crate) fn time_mut(&mut self) -> &mut Tick {
         match self {
             GlobalEvent::PhraseStart { time }
             | GlobalEvent::PhraseEnd { time }
+            | GlobalEvent::MusicStart { time }
+            | GlobalEvent::End { time }
+            | GlobalEvent::Coda { time }
+            | GlobalEvent::BigRockEnding { time }
+            | GlobalEvent::Solo { time }
             | GlobalEvent::Section { time, .. }
             | GlobalEvent::Lyric { time, .. }
+            | GlobalEvent::Crowd { time, .. }
+            | GlobalEvent::Lighting { time, .. }
             | GlobalEvent::Other { time, .. } => time,
         }
     }
@@ -73,13 +187,7 @@ impl<'a> GlobalEvent<'a> {
 
 impl<'a> Display for GlobalEvent<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            GlobalEvent::PhraseStart { time } => writeln!(f, "  {time} = E \"phrase_start\""),
-            GlobalEvent::PhraseEnd { time } => writeln!(f, "  {time} = E \"phrase_end\""),
-            GlobalEvent::Section { time, name } => writeln!(f, "  {time} = E \"section {name}\""),
-            GlobalEvent::Lyric { time, text } => writeln!(f, "  {time} = E \"lyric {text}\""),
-            GlobalEvent::Other { time, value } => write!(f, "  {time} = E \"{value}\""),
-        }
+        writeln!(f, "  {} = E \"{}\"", self.time(), self.text())
     }
 }
 
@@ -95,4 +203,88 @@ mod tests {
         GlobalEvent::parse("38592 = E \"lyric I\"").unwrap();
         GlobalEvent::parse("40512 = E \"phrase_end\"").unwrap();
     }
+
+    #[test]
+    fn test_typed_global_events() {
+        let time = Tick::new(768);
+        for (text, event) in [
+            ("music_start", GlobalEvent::MusicStart { time }),
+            ("end", GlobalEvent::End { time }),
+            ("coda", GlobalEvent::Coda { time }),
+            ("big_rock_ending", GlobalEvent::BigRockEnding { time }),
+            ("solo", GlobalEvent::Solo { time }),
+            (
+                "crowd_lighters_fast",
+                GlobalEvent::Crowd {
+                    time,
+                    kind: "lighters_fast".into(),
+                },
+            ),
+            (
+                "lighting (strobe_fast)",
+                GlobalEvent::Lighting {
+                    time,
+                    kind: "strobe_fast".into(),
+                },
+            ),
+            (
+                "lighting ()",
+                GlobalEvent::Lighting {
+                    time,
+                    kind: "".into(),
+                },
+            ),
+            (
+                "ending",
+                GlobalEvent::Other {
+                    time,
+                    value: "ending".into(),
+                },
+            ),
+        ] {
+            let line = format!("768 = E \"{text}\"");
+            let parsed = GlobalEvent::parse(&line).unwrap().1;
+            assert_eq!(parsed, event);
+            assert_eq!(parsed.to_string(), format!("  {line}\n"));
+        }
+    }
+
+    #[test]
+    fn test_event_quoting() {
+        fn parse(line: &str) -> GlobalEvent<'_> {
+            GlobalEvent::parse(line).unwrap().1
+        }
+
+        assert_eq!(parse("0 = E end"), GlobalEvent::End { time: Tick::ZERO });
+        assert_eq!(
+            parse("0 = E section Verse 1"),
+            GlobalEvent::Section {
+                time: Tick::ZERO,
+                name: "Verse 1".into(),
+            }
+        );
+        let lyric = GlobalEvent::Lyric {
+            time: Tick::ZERO,
+            text: "say \"hi\"".into(),
+        };
+        assert_eq!(parse("0 = E \"lyric say \"hi\"\""), lyric);
+        assert_eq!(lyric.to_string(), "  0 = E \"lyric say \"hi\"\"\n");
+
+        for text in [
+            "",
+            " ",
+            "\"",
+            "こんにちは",
+            "ça va?",
+            "a\\b",
+            "a\\\"b",
+            "\"quoted\"",
+        ] {
+            let event = GlobalEvent::Lyric {
+                time: Tick::ZERO,
+                text: text.into(),
+            };
+            assert_eq!(parse(event.to_string().trim()), event);
+        }
+    }
 }
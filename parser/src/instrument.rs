@@ -0,0 +1,155 @@
+use std::fmt::Display;
+
+/// Difficulty part of a track name such as `ExpertSingle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Expert,
+    Hard,
+    Medium,
+    Easy,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Expert,
+        Difficulty::Hard,
+        Difficulty::Medium,
+        Difficulty::Easy,
+    ];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Difficulty::Expert => "Expert",
+            Difficulty::Hard => "Hard",
+            Difficulty::Medium => "Medium",
+            Difficulty::Easy => "Easy",
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Instrument part of a track name such as `ExpertSingle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Instrument {
+    Single,
+    DoubleGuitar,
+    DoubleBass,
+    DoubleRhythm,
+    Keyboard,
+    Drums,
+    GhlGuitar,
+    GhlBass,
+    GhlRhythm,
+    GhlCoop,
+}
+
+impl Instrument {
+    pub const ALL: [Instrument; 10] = [
+        Instrument::Single,
+        Instrument::DoubleGuitar,
+        Instrument::DoubleBass,
+        Instrument::DoubleRhythm,
+        Instrument::Keyboard,
+        Instrument::Drums,
+        Instrument::GhlGuitar,
+        Instrument::GhlBass,
+        Instrument::GhlRhythm,
+        Instrument::GhlCoop,
+    ];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Instrument::Single => "Single",
+            Instrument::DoubleGuitar => "DoubleGuitar",
+            Instrument::DoubleBass => "DoubleBass",
+            Instrument::DoubleRhythm => "DoubleRhythm",
+            Instrument::Keyboard => "Keyboard",
+            Instrument::Drums => "Drums",
+            Instrument::GhlGuitar => "GHLGuitar",
+            Instrument::GhlBass => "GHLBass",
+            Instrument::GhlRhythm => "GHLRhythm",
+            Instrument::GhlCoop => "GHLCoop",
+        }
+    }
+
+    /// Whether the instrument is played on a five-fret guitar.
+    #[must_use]
+    pub fn is_five_fret(self) -> bool {
+        matches!(
+            self,
+            Instrument::Single
+                | Instrument::DoubleGuitar
+                | Instrument::DoubleBass
+                | Instrument::DoubleRhythm
+                | Instrument::Keyboard
+        )
+    }
+
+    /// Whether the given `N` value is a playable gem rather than a modifier
+    /// flag such as forced, tap or cymbal.
+    #[must_use]
+    pub fn is_gem(self, fret: u32) -> bool {
+        match self {
+            Instrument::Drums => matches!(fret, 0..=5 | 32),
+            Instrument::GhlGuitar
+            | Instrument::GhlBass
+            | Instrument::GhlRhythm
+            | Instrument::GhlCoop => matches!(fret, 0..=4 | 7 | 8),
+            Instrument::Single
+            | Instrument::DoubleGuitar
+            | Instrument::DoubleBass
+            | Instrument::DoubleRhythm
+            | Instrument::Keyboard => matches!(fret, 0..=4 | 7),
+        }
+    }
+}
+
+impl Display for Instrument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Split a track name into its difficulty and instrument, if it is one of the
+/// standard names.
+#[must_use]
+pub fn parse_track_name(name: &str) -> Option<(Difficulty, Instrument)> {
+    Difficulty::ALL.into_iter().find_map(|difficulty| {
+        let rest = name.strip_prefix(difficulty.as_str())?;
+        Instrument::ALL
+            .into_iter()
+            .find(|instrument| instrument.as_str() == rest)
+            .map(|instrument| (difficulty, instrument))
+    })
+}
+
+/// Build the standard track name for a difficulty and instrument.
+#[must_use]
+pub fn track_name(difficulty: Difficulty, instrument: Instrument) -> String {
+    format!("{difficulty}{instrument}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_track_name() {
+        assert_eq!(
+            parse_track_name("ExpertSingle"),
+            Some((Difficulty::Expert, Instrument::Single))
+        );
+        assert_eq!(
+            parse_track_name("EasyGHLBass"),
+            Some((Difficulty::Easy, Instrument::GhlBass))
+        );
+        assert_eq!(parse_track_name("ExpertKazoo"), None);
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt::Display;
+
+use crate::{chart::Chart, global_event::GlobalEvent};
+
+/// Where a chart ends compared to its audio, from [`Chart::check_length`].
+/// All times are in seconds into the audio, so they include the `Offset`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LengthReport {
+    /// End of the last note, sustain, phrase or event.
+    pub chart_end: f64,
+    /// Time of the first `end` event, if there is one.
+    pub end_event: Option<f64>,
+    /// Duration of the audio, if known.
+    pub audio_length: Option<f64>,
+}
+
+impl LengthReport {
+    /// Whether something in the chart happens after the audio has ended.
+    #[must_use]
+    pub fn runs_past_audio(&self) -> bool {
+        self.audio_length
+            .is_some_and(|audio_length| self.chart_end > audio_length)
+    }
+}
+
+pub(crate) fn check_length(chart: &Chart, audio_length: Option<f64>) -> LengthReport {
+    let tempo = chart.tempo_map();
+    let offset = chart.song().offset().unwrap_or(0.0);
+    let end_event = chart
+        .global_events()
+        .events()
+        .iter()
+        .find_map(|event| match event {
+            GlobalEvent::End { time } => Some(*time),
+            _ => None,
+        });
+    LengthReport {
+        chart_end: tempo.seconds_at(chart.last_tick()) + offset,
+        end_event: end_event.map(|time| tempo.seconds_at(time) + offset),
+        audio_length,
+    }
+}
+
+impl Display for LengthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Chart ends at {:.3} s", self.chart_end)?;
+        match self.audio_length {
+            Some(audio_length) if self.runs_past_audio() => writeln!(
+                f,
+                ", {:.3} s after the audio ends at {audio_length:.3} s",
+                self.chart_end - audio_length
+            )?,
+            Some(audio_length) => writeln!(f, ", audio ends at {audio_length:.3} s")?,
+            None => writeln!(f, ", audio length unknown")?,
+        }
+        match self.end_event {
+            Some(end_event) => writeln!(f, "End event at {end_event:.3} s"),
+            None => writeln!(f, "No end event"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_check_length() {
+        let text = "[Song]
+{
+  Resolution = 192
+  Offset = 0.5
+}
+[SyncTrack]
+{
+  0 = B 120000
+  768 = B 60000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  960 = N 0 192
+}
+";
+        let chart = Chart::parse(text).unwrap().1;
+        let report = chart.check_length(Some(4.0));
+        assert!((report.chart_end - 4.5).abs() < 1e-9);
+        assert_eq!(report.end_event, None);
+        assert!(report.runs_past_audio());
+        assert!(!chart.check_length(None).runs_past_audio());
+
+        let text = text.replace("\"section Intro\"", "\"section Intro\"\n  1152 = E \"end\"");
+        let chart = Chart::parse(&text).unwrap().1;
+        let report = chart.check_length(Some(6.0));
+        assert_eq!(report.end_event, Some(4.5));
+        assert!(!report.runs_past_audio());
+    }
+}
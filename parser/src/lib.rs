@@ -1,15 +1,66 @@
 #![forbid(unsafe_code)]
 
+pub mod audio;
 pub mod chart;
 mod components;
+pub mod crop;
+pub mod diff;
+pub mod duration;
 mod events;
 mod global_event;
+pub mod instrument;
+pub mod length;
+pub mod medley;
+pub mod merge;
+pub mod normalize;
+pub mod offset;
+pub mod quantize;
+pub mod reduce;
+pub mod remap;
+mod rescale;
 mod song;
 mod song_property;
+pub mod star_power;
+pub mod stats;
+pub mod sustain;
 mod sync_track;
 mod sync_track_event;
+pub mod tempo;
+mod tick;
+mod time_edit;
+pub mod time_signature;
 mod track;
 mod track_event;
+pub mod transplant;
+pub mod validate;
 
+pub use audio::{AudioReport, MissingAudio};
 pub use chart::Chart;
+pub use crop::Crop;
+pub use diff::{Change, ChartDiff, Difference};
+pub use events::Events;
+pub use global_event::GlobalEvent;
+pub use length::LengthReport;
+pub use medley::MedleyError;
+pub use merge::{Conflict, Merge};
 pub use nom::Err;
+pub use normalize::{NormalizeReport, SectionChanges};
+pub use offset::OffsetError;
+pub use quantize::{MovedEvent, QuantizeReport, RemovedDuplicates};
+pub use reduce::{ReduceError, ReduceOptions, ReduceReport, ReducedTrack, ReductionLevel};
+pub use remap::{LaneRemap, RemapError};
+pub use rescale::RescaleError;
+pub use song::{AudioStream, Song};
+pub use song_property::SongProperty;
+pub use star_power::{CopiedPhrases, StarPowerError, StarPowerReport};
+pub use stats::{ChartStats, TrackStats};
+pub use sustain::{SustainChanges, SustainOptions, SustainReport};
+pub use sync_track::SyncTrack;
+pub use sync_track_event::SyncTrackEvent;
+pub use tempo::TempoMap;
+pub use tick::Tick;
+pub use time_signature::TimeSignatureMap;
+pub use track::Track;
+pub use track_event::TrackEvent;
+pub use transplant::{OffGridEvent, TransplantError, TransplantReport};
+pub use validate::{Diagnostic, Problem, Severity};
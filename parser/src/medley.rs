@@ -0,0 +1,266 @@
+use std::{borrow::Cow, fmt::Display};
+
+use crate::{
+    chart::Chart,
+    global_event::GlobalEvent,
+    rescale::{lcm, RescaleError},
+    sync_track_event::SyncTrackEvent,
+    tempo::DEFAULT_BPM,
+    tick::Tick,
+};
+
+/// Error returned by [`Chart::concatenate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MedleyError {
+    /// No charts were given.
+    Empty,
+    /// A chart has a resolution of 0, so there is no common resolution.
+    ZeroResolution,
+    /// The common resolution or a shifted event does not fit.
+    Rescale(RescaleError),
+}
+
+impl From<RescaleError> for MedleyError {
+    fn from(error: RescaleError) -> Self {
+        MedleyError::Rescale(error)
+    }
+}
+
+impl Display for MedleyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MedleyError::Empty => write!(f, "no charts to concatenate"),
+            MedleyError::ZeroResolution => {
+                write!(f, "cannot concatenate a chart with resolution 0")
+            }
+            MedleyError::Rescale(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MedleyError {}
+
+pub(crate) fn concatenate(charts: Vec<Chart<'_>>) -> Result<Chart<'_>, MedleyError> {
+    let resolution = common_resolution(&charts)?;
+    let mut medley: Option<Chart> = None;
+    for (index, mut chart) in charts.into_iter().enumerate() {
+        chart.multiply_with_threshold(resolution / chart.resolution(), 0)?;
+        restate_tempo(&mut chart);
+        let name = match chart.song().name() {
+            Some(name) if !name.is_empty() => name.into_owned(),
+            _ => format!("Song {}", index + 1),
+        };
+        // the song's own section at its start gives way to its name
+        let events = chart.global_events_mut().events_mut();
+        match events
+            .iter_mut()
+            .find(|event| matches!(event, GlobalEvent::Section { time, .. } if *time == Tick::ZERO))
+        {
+            Some(GlobalEvent::Section { name: section, .. }) => *section = Cow::Owned(name),
+            _ => events.insert(
+                0,
+                GlobalEvent::Section {
+                    time: Tick::ZERO,
+                    name: Cow::Owned(name),
+                },
+            ),
+        }
+        match &mut medley {
+            None => medley = Some(chart),
+            Some(medley) => {
+                let end = medley.last_tick();
+                let signatures = medley.time_signature_map();
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let start = signatures
+                    .measure_start(end)
+                    .saturating_add(signatures.measure_length(end).round() as u32);
+                chart.insert_time(Tick::ZERO, start.get())?;
+                // anchors count from the start of their own song's audio
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let microseconds =
+                    (medley.tempo_map().seconds_at(start) * 1_000_000.0).round() as u32;
+                for event in chart.synctrack_mut().events_mut() {
+                    if let SyncTrackEvent::Anchor { value, .. } = event {
+                        *value = value.saturating_add(microseconds);
+                    }
+                }
+                append(medley, chart);
+            }
+        }
+    }
+    medley.ok_or(MedleyError::Empty)
+}
+
+/// Least common multiple of the resolutions of all charts.
+fn common_resolution(charts: &[Chart]) -> Result<u32, MedleyError> {
+    charts
+        .iter()
+        .map(Chart::resolution)
+        .try_fold(1, |common, resolution| {
+            if resolution == 0 {
+                return Err(MedleyError::ZeroResolution);
+            }
+            Ok(lcm(common, resolution)?)
+        })
+}
+
+/// Make the tempo and time signature at tick 0 explicit, so that they are not
+/// inherited from the previous song.
+fn restate_tempo(chart: &mut Chart) {
+    let events = chart.synctrack_mut().events_mut();
+    let at_start = |events: &[SyncTrackEvent], is_kind: fn(&SyncTrackEvent) -> bool| {
+        events
+            .iter()
+            .any(|event| is_kind(event) && event.time() == Tick::ZERO)
+    };
+    if !at_start(events, |event| matches!(event, SyncTrackEvent::Bpm { .. })) {
+        events.insert(
+            0,
+            SyncTrackEvent::Bpm {
+                time: Tick::ZERO,
+                value: DEFAULT_BPM,
+            },
+        );
+    }
+    if !at_start(events, |event| {
+        matches!(event, SyncTrackEvent::TimeSignature { .. })
+    }) {
+        events.insert(
+            0,
+            SyncTrackEvent::TimeSignature {
+                time: Tick::ZERO,
+                value1: 4,
+                value2: None,
+            },
+        );
+    }
+}
+
+/// Move every event of `chart` to the end of `medley`, merging tracks by name.
+fn append<'a>(medley: &mut Chart<'a>, mut chart: Chart<'a>) {
+    medley
+        .synctrack_mut()
+        .events_mut()
+        .append(chart.synctrack_mut().events_mut());
+    medley
+        .global_events_mut()
+        .events_mut()
+        .append(chart.global_events_mut().events_mut());
+    for mut track in chart.tracks_mut().drain(..) {
+        match medley
+            .tracks_mut()
+            .iter_mut()
+            .find(|existing| existing.name() == track.name())
+        {
+            Some(existing) => existing.events_mut().append(track.events_mut()),
+            None => medley.tracks_mut().push(track),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_concatenate() {
+        let first = Chart::parse(
+            "[Song]
+{
+  Name = \"First\"
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 3
+  0 = B 100000
+}
+[Events]
+{
+  96 = E \"lyric la\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  600 = N 1 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        let second = Chart::parse(
+            "[Song]
+{
+  Resolution = 480
+}
+[SyncTrack]
+{
+  480 = B 150000
+  480 = A 500000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  240 = N 2 0
+}
+[HardSingle]
+{
+  0 = N 0 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        let medley = Chart::concatenate(vec![first, second]).unwrap();
+        assert_eq!(
+            medley.to_string(),
+            "[Song]
+{
+  Resolution = 960
+  Name = \"First\"
+}
+[SyncTrack]
+{
+  0 = TS 3
+  0 = B 100000
+  5760 = TS 4
+  5760 = B 120000
+  6720 = B 150000
+  6720 = A 4100000
+}
+[Events]
+{
+  0 = E \"section First\"
+  480 = E \"lyric la\"
+  5760 = E \"section Song 2\"
+}
+
+[ExpertSingle]
+{
+  0 = N 0 0
+  3000 = N 1 0
+  6240 = N 2 0
+}
+[HardSingle]
+{
+  5760 = N 0 0
+}
+"
+        );
+        // "section Intro" gave way to the song's name at the boundary
+        let sections = medley
+            .global_events()
+            .events()
+            .iter()
+            .filter(
+                |event| matches!(event, GlobalEvent::Section { time, .. } if time.get() == 5760),
+            )
+            .count();
+        assert_eq!(sections, 1);
+        assert_eq!(Chart::concatenate(vec![]), Err(MedleyError::Empty));
+    }
+}
@@ -0,0 +1,454 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+    chart::Chart, diff::at_common_resolution, events::Events, global_event::GlobalEvent,
+    rescale::RescaleError, song::Song, song_property::SongProperty, sync_track::SyncTrack,
+    sync_track_event::SyncTrackEvent, tick::Tick, track::Track, track_event::TrackEvent,
+};
+
+/// An event that both sides of a [`Chart::merge`] changed differently. The
+/// merged chart keeps our version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflict {
+    /// Name of the section, without brackets.
+    pub section: String,
+    pub tick: Tick,
+    /// The event as written in each chart, or `None` where it is missing.
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+/// Result of [`Chart::merge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge<'a> {
+    pub chart: Chart<'a>,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Identity of an event for merging: events with the same key are versions
+/// of each other, so a changed sustain or a renamed section is an edit rather
+/// than a removal and an addition.
+type Key = (Tick, u8, u32, String);
+
+fn sync_track_key(event: &SyncTrackEvent) -> Key {
+    (event.time(), event.type_rank(), 0, String::new())
+}
+
+fn global_event_key(event: &GlobalEvent) -> Key {
+    match event {
+        GlobalEvent::Section { time, .. } => (*time, 0, 0, String::new()),
+        GlobalEvent::PhraseStart { time } => (*time, 1, 0, String::new()),
+        GlobalEvent::Lyric { time, .. } => (*time, 2, 0, String::new()),
+        GlobalEvent::PhraseEnd { time } => (*time, 3, 0, String::new()),
+        event => (event.time(), 4, 0, event.text().into_owned()),
+    }
+}
+
+fn track_event_key(event: &TrackEvent) -> Key {
+    match event {
+        TrackEvent::Note { time, fret, .. } => (*time, 0, *fret, String::new()),
+        TrackEvent::Special { time, kind, .. } => (*time, 1, *kind, String::new()),
+        TrackEvent::Event { time, value } => (*time, 2, 0, value.to_string()),
+    }
+}
+
+fn song_property_key(property: &SongProperty) -> Key {
+    (Tick::ZERO, 0, 0, property.name().to_string())
+}
+
+/// Three-way merge of one section's events. Events are grouped by `key`, and
+/// the events of a group are matched in the order they are written, so two
+/// sections at one tick are two versions rather than one. When both sides
+/// change how many events a group has, the group conflicts as a whole. The
+/// merged events keep our order, followed by events only they added.
+fn merge_events<E: Clone + PartialEq + Display>(
+    section: &str,
+    [base, ours, theirs]: [&[E]; 3],
+    key: impl Fn(&E) -> Key,
+    conflicts: &mut Vec<Conflict>,
+) -> Vec<E> {
+    let by_key = |events: &[E]| -> BTreeMap<Key, Vec<E>> {
+        let mut groups: BTreeMap<Key, Vec<E>> = BTreeMap::new();
+        for event in events {
+            groups.entry(key(event)).or_default().push(event.clone());
+        }
+        groups
+    };
+    let (base_events, our_events, their_events) = (by_key(base), by_key(ours), by_key(theirs));
+    let mut order: BTreeMap<Key, usize> = BTreeMap::new();
+    for (index, event) in ours.iter().chain(theirs).enumerate() {
+        order.entry(key(event)).or_insert(index);
+    }
+    let describe = |events: &[E]| {
+        (!events.is_empty()).then(|| {
+            events
+                .iter()
+                .map(|event| event.to_string().trim().to_string())
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    };
+    let mut conflict = |tick: Tick, [base, ours, theirs]: [&[E]; 3]| {
+        conflicts.push(Conflict {
+            section: section.to_string(),
+            tick,
+            base: describe(base),
+            ours: describe(ours),
+            theirs: describe(theirs),
+        });
+    };
+
+    let mut merged = vec![];
+    for (key, index) in order {
+        let [base, ours, theirs] = [&base_events, &our_events, &their_events]
+            .map(|events| events.get(&key).map_or(&[][..], Vec::as_slice));
+        if ours.len() != base.len() && theirs.len() != base.len() && ours != theirs {
+            conflict(key.0, [base, ours, theirs]);
+            merged.extend(ours.iter().map(|event| (key.0, index, event.clone())));
+            continue;
+        }
+        let count = ours.len().max(theirs.len()).max(base.len());
+        for occurrence in 0..count {
+            let [base, ours, theirs] = [base, ours, theirs].map(|group| group.get(occurrence));
+            let event = if ours == theirs || theirs == base {
+                ours
+            } else if ours == base {
+                theirs
+            } else {
+                let versions =
+                    [base, ours, theirs].map(|event| event.map_or(&[][..], std::slice::from_ref));
+                conflict(key.0, versions);
+                ours
+            };
+            if let Some(event) = event {
+                merged.push((key.0, index, event.clone()));
+            }
+        }
+    }
+    merged.sort_by_key(|(tick, index, _)| (*tick, *index));
+    merged.into_iter().map(|(_, _, event)| event).collect()
+}
+
+/// Number of events of a track, as written in a conflict.
+fn describe_track(track: Option<&Track>) -> Option<String> {
+    track.map(|track| match track.events().len() {
+        1 => "1 event".to_string(),
+        count => format!("{count} events"),
+    })
+}
+
+pub(crate) fn merge<'a>(
+    base: &Chart<'a>,
+    ours: &Chart<'a>,
+    theirs: &Chart<'a>,
+) -> Result<Merge<'a>, RescaleError> {
+    let resolution = ours.resolution();
+    let [base, ours, theirs] = at_common_resolution([base, ours, theirs])?;
+    let mut conflicts = vec![];
+
+    let properties = merge_events(
+        "Song",
+        [
+            base.song().properties(),
+            ours.song().properties(),
+            theirs.song().properties(),
+        ],
+        song_property_key,
+        &mut conflicts,
+    );
+    let synctrack = merge_events(
+        "SyncTrack",
+        [
+            base.synctrack().events(),
+            ours.synctrack().events(),
+            theirs.synctrack().events(),
+        ],
+        sync_track_key,
+        &mut conflicts,
+    );
+    let global_events = merge_events(
+        "Events",
+        [
+            base.global_events().events(),
+            ours.global_events().events(),
+            theirs.global_events().events(),
+        ],
+        global_event_key,
+        &mut conflicts,
+    );
+
+    let find = |chart: &'_ Chart<'a>, name: &str| -> Option<Track<'a>> {
+        chart
+            .tracks()
+            .iter()
+            .find(|track| track.name() == name)
+            .cloned()
+    };
+    let mut names: Vec<&str> = vec![];
+    for track in ours.tracks().iter().chain(theirs.tracks()) {
+        if !names.contains(&track.name()) {
+            names.push(track.name());
+        }
+    }
+    let mut tracks = vec![];
+    for name in names {
+        let [base_track, our_track, their_track] =
+            [&base, &ours, &theirs].map(|chart| find(chart, name));
+        let events = |track: &Option<Track<'a>>| {
+            track
+                .as_ref()
+                .map(|track| track.events().to_vec())
+                .unwrap_or_default()
+        };
+        // a track deleted on one side stays deleted if the other side left it
+        // alone, and conflicts as a whole if the other side edited it
+        if let Some(base_track) = &base_track {
+            if our_track.is_none() || their_track.is_none() {
+                let edited = |track: &Option<Track<'a>>| {
+                    track.as_ref().is_some_and(|track| track != base_track)
+                };
+                if edited(&our_track) || edited(&their_track) {
+                    conflicts.push(Conflict {
+                        section: name.to_string(),
+                        tick: Tick::ZERO,
+                        base: describe_track(Some(base_track)),
+                        ours: describe_track(our_track.as_ref()),
+                        theirs: describe_track(their_track.as_ref()),
+                    });
+                    // the merged chart keeps our version
+                    tracks.extend(our_track);
+                }
+                continue;
+            }
+        }
+        let events = merge_events(
+            name,
+            [
+                &events(&base_track),
+                &events(&our_track),
+                &events(&their_track),
+            ],
+            track_event_key,
+            &mut conflicts,
+        );
+        tracks.push(Track::new(name.to_string(), events));
+    }
+
+    let song = Song::new(ours.resolution(), properties);
+    let mut chart = Chart::new(
+        song,
+        SyncTrack::new(synctrack),
+        Events::new(global_events),
+        tracks,
+    );
+    // back to our resolution, unless their edits need the finer one
+    chart.rescale_down_to(resolution)?;
+    Ok(Merge { chart, conflicts })
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let version = |event: &Option<String>| event.clone().unwrap_or_else(|| "none".into());
+        writeln!(
+            f,
+            "[{}] tick {}: ours {:?}, theirs {:?} (base {:?})",
+            self.section,
+            self.tick,
+            version(&self.ours),
+            version(&self.theirs),
+            version(&self.base)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const BASE: &str = "[Song]
+{
+  Name = \"Song\"
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+  768 = E \"lyric Hel-\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  192 = N 1 0
+  384 = N 2 0
+}
+[HardSingle]
+{
+  0 = N 0 0
+}
+";
+
+    #[test]
+    fn test_merge() {
+        let base = Chart::parse(BASE).unwrap().1;
+        // ours renames the intro, edits a sustain and rescales to 384
+        let ours_text = BASE
+            .replace("section Intro", "section Opening")
+            .replace("192 = N 1 0", "192 = N 1 96");
+        let mut ours = Chart::parse(&ours_text).unwrap().1;
+        ours.multiply(2).unwrap();
+        // theirs fixes a lyric, deletes a track and edits the same note
+        let theirs_text = BASE
+            .replace("lyric Hel-", "lyric Hel")
+            .replace("[HardSingle]\n{\n  0 = N 0 0\n}\n", "")
+            .replace("192 = N 1 0", "192 = N 1 48")
+            .replace("384 = N 2 0", "384 = N 2 0\n  576 = N 3 0");
+        let theirs = Chart::parse(&theirs_text).unwrap().1;
+
+        let merge = Chart::merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(
+            merge.conflicts,
+            vec![Conflict {
+                section: "ExpertSingle".to_string(),
+                tick: Tick::new(384),
+                base: Some("384 = N 1 0".to_string()),
+                ours: Some("384 = N 1 192".to_string()),
+                theirs: Some("384 = N 1 96".to_string()),
+            }]
+        );
+        assert_eq!(
+            merge.chart.to_string(),
+            "[Song]
+{
+  Resolution = 384
+  Name = \"Song\"
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Opening\"
+  1536 = E \"lyric Hel\"
+}
+
+[ExpertSingle]
+{
+  0 = N 0 0
+  384 = N 1 192
+  768 = N 2 0
+  1152 = N 3 0
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_merge_same_tick() {
+        let base = BASE.replace(
+            "  768 = E \"lyric Hel-\"\n",
+            "  768 = E \"section Verse\"\n  768 = E \"section Chorus\"\n",
+        );
+        let merged = |ours: &str, theirs: &str| {
+            let [base, ours, theirs] =
+                [&base, ours, theirs].map(|text| Chart::parse(text).unwrap().1);
+            let merge = Chart::merge(&base, &ours, &theirs).unwrap();
+            (merge.chart.to_string(), merge.conflicts)
+        };
+
+        // both sections at 768 survive, and each edit lands on its own one
+        let (chart, conflicts) = merged(
+            &base.replace("section Verse", "section Verse 1"),
+            &base.replace("section Chorus", "section Chorus 1"),
+        );
+        assert!(conflicts.is_empty());
+        assert!(chart.contains("  768 = E \"section Verse 1\"\n  768 = E \"section Chorus 1\"\n"));
+
+        // each side removes a different one, so which one is left is unclear
+        let (chart, conflicts) = merged(
+            &base.replace("  768 = E \"section Verse\"\n", ""),
+            &base.replace("  768 = E \"section Chorus\"\n", ""),
+        );
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                section: "Events".to_string(),
+                tick: Tick::new(768),
+                base: Some("768 = E \"section Verse\"; 768 = E \"section Chorus\"".to_string()),
+                ours: Some("768 = E \"section Chorus\"".to_string()),
+                theirs: Some("768 = E \"section Verse\"".to_string()),
+            }]
+        );
+        assert!(chart.contains("  768 = E \"section Chorus\"\n}"));
+    }
+
+    #[test]
+    fn test_merge_deleted_track() {
+        let base = Chart::parse(BASE).unwrap().1;
+        let without_hard = BASE.replace("[HardSingle]\n{\n  0 = N 0 0\n}\n", "");
+        let ours = Chart::parse(&without_hard).unwrap().1;
+        let edited_hard = BASE.replace(
+            "[HardSingle]\n{\n  0 = N 0 0\n",
+            "[HardSingle]\n{\n  0 = N 0 0\n  192 = N 1 0\n",
+        );
+        let theirs = Chart::parse(&edited_hard).unwrap().1;
+
+        let merge = Chart::merge(&base, &ours, &theirs).unwrap();
+        let conflict = Conflict {
+            section: "HardSingle".to_string(),
+            tick: Tick::ZERO,
+            base: Some("1 event".to_string()),
+            ours: None,
+            theirs: Some("2 events".to_string()),
+        };
+        assert_eq!(merge.conflicts, vec![conflict]);
+        assert!(!merge.chart.to_string().contains("[HardSingle]"));
+
+        let merge = Chart::merge(&base, &theirs, &ours).unwrap();
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(merge.chart.to_string().contains("  192 = N 1 0\n}\n"));
+
+        let merge = Chart::merge(&base, &ours, &base).unwrap();
+        assert!(merge.conflicts.is_empty());
+        assert!(!merge.chart.to_string().contains("[HardSingle]"));
+    }
+
+    #[test]
+    fn test_merge_resolutions() {
+        let base = Chart::parse(BASE).unwrap().1;
+        let mut theirs = base.clone();
+        theirs.multiply_with_threshold(5, 0).unwrap();
+        let theirs_text = theirs
+            .to_string()
+            .replace("1920 = N 2 0", "1920 = N 2 0\n  2400 = N 3 0");
+        let theirs = Chart::parse(&theirs_text).unwrap().1;
+
+        // written at our resolution, as their note falls on a tick there
+        let merge = Chart::merge(&base, &base, &theirs).unwrap();
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.chart.resolution(), 192);
+        assert!(merge
+            .chart
+            .to_string()
+            .contains("  384 = N 2 0\n  480 = N 3 0\n"));
+
+        // a note between our ticks keeps the common resolution
+        let theirs_text = theirs_text.replace("2400 = N 3 0", "2401 = N 3 0");
+        let theirs = Chart::parse(&theirs_text).unwrap().1;
+        let merge = Chart::merge(&base, &base, &theirs).unwrap();
+        assert_eq!(merge.chart.resolution(), 960);
+        assert!(merge
+            .chart
+            .to_string()
+            .contains("  1920 = N 2 0\n  2401 = N 3 0\n"));
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+use crate::{chart::Chart, global_event::GlobalEvent};
+
+/// Changes made to a single section by [`Chart::normalize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionChanges {
+    /// Name of the section, without brackets.
+    pub section: String,
+    /// Number of events that came before an event they should follow.
+    pub reordered: usize,
+    pub duplicates_removed: usize,
+}
+
+/// Summary of [`Chart::normalize`], listing only the sections that changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NormalizeReport {
+    pub sections: Vec<SectionChanges>,
+}
+
+impl NormalizeReport {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        section: &str,
+        (reordered, duplicates_removed): (usize, usize),
+    ) {
+        if reordered > 0 || duplicates_removed > 0 {
+            self.sections.push(SectionChanges {
+                section: section.to_string(),
+                reordered,
+                duplicates_removed,
+            });
+        }
+    }
+}
+
+/// Stably sort `events` by `key` and drop exact duplicates, returning the
+/// number of reordered and removed events.
+pub(crate) fn normalize_events<T: PartialEq, K: Ord>(
+    events: &mut Vec<T>,
+    key: impl Fn(&T) -> K,
+) -> (usize, usize) {
+    let mut reordered = 0;
+    let mut max_key = None;
+    for event in events.iter() {
+        let event_key = key(event);
+        if max_key.as_ref().is_some_and(|max| event_key < *max) {
+            reordered += 1;
+        } else {
+            max_key = Some(event_key);
+        }
+    }
+    events.sort_by_key(&key);
+    let original_len = events.len();
+    let mut kept: Vec<T> = Vec::with_capacity(original_len);
+    for event in events.drain(..) {
+        // duplicates share a key, so only the trailing run of equal keys can hold one
+        let event_key = key(&event);
+        let duplicate = kept
+            .iter()
+            .rev()
+            .take_while(|other| key(other) == event_key)
+            .any(|other| *other == event);
+        if !duplicate {
+            kept.push(event);
+        }
+    }
+    let removed = original_len - kept.len();
+    *events = kept;
+    (reordered, removed)
+}
+
+pub(crate) fn normalize(chart: &mut Chart) -> NormalizeReport {
+    let mut report = NormalizeReport::default();
+    report.record(
+        "SyncTrack",
+        normalize_events(chart.synctrack_mut().events_mut(), |event| {
+            (event.time(), event.type_rank())
+        }),
+    );
+    report.record(
+        "Events",
+        normalize_events(chart.global_events_mut().events_mut(), GlobalEvent::time),
+    );
+    for track in chart.tracks_mut() {
+        let changes = normalize_events(track.events_mut(), |event| {
+            (event.time(), event.type_rank())
+        });
+        report.record(track.name(), changes);
+    }
+    report
+}
+
+impl Display for NormalizeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for changes in &self.sections {
+            writeln!(
+                f,
+                "[{}]: {} reordered, {} duplicates removed",
+                changes.section, changes.reordered, changes.duplicates_removed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::track::Track;
+
+    #[test]
+    fn test_normalize_track() {
+        let mut track = Track::parse(
+            "[ExpertSingle]
+{
+  192 = E solo
+  192 = N 1 0
+  0 = N 0 0
+  192 = S 2 96
+  192 = N 1 0
+}",
+        )
+        .unwrap()
+        .1;
+        let changes = normalize_events(track.events_mut(), |event| {
+            (event.time(), event.type_rank())
+        });
+        assert_eq!(changes, (4, 1));
+        assert_eq!(
+            track.to_string(),
+            "[ExpertSingle]
+{
+  0 = N 0 0
+  192 = N 1 0
+  192 = S 2 96
+  192 = E solo
+}
+"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::fmt::Display;
+
+use crate::{
+    chart::Chart, global_event::GlobalEvent, rescale::RescaleError,
+    sync_track_event::SyncTrackEvent, tick::Tick, track_event::TrackEvent,
+};
+
+/// Error returned by [`Chart::shift_audio`] and [`Chart::bake_offset`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum OffsetError {
+    /// The `Offset` property is not a number of seconds.
+    InvalidOffset(String),
+    /// Moving events earlier needs more silence before the first event than
+    /// the chart has.
+    LeadIn {
+        requested_ms: f64,
+        available_ms: f64,
+    },
+    /// The lead-in would need a BPM that cannot be stored.
+    Tempo { ms: f64 },
+    /// Making room for the lead-in measure overflowed.
+    Rescale(RescaleError),
+}
+
+impl Display for OffsetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OffsetError::InvalidOffset(value) => write!(f, "invalid offset {value}"),
+            OffsetError::LeadIn {
+                requested_ms,
+                available_ms,
+            } => write!(
+                f,
+                "cannot move events {requested_ms} ms earlier, the first event is {available_ms} ms in"
+            ),
+            OffsetError::Tempo { ms } => write!(f, "no BPM gives a {ms} ms lead-in"),
+            OffsetError::Rescale(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for OffsetError {}
+
+pub(crate) fn shift_audio(chart: &mut Chart, delta_ms: f64) -> Result<(), OffsetError> {
+    if delta_ms > 0.0 {
+        insert_lead_in(chart, delta_ms)
+    } else if delta_ms < 0.0 {
+        shorten_lead_in(chart, -delta_ms)
+    } else {
+        Ok(())
+    }
+}
+
+pub(crate) fn bake_offset(chart: &mut Chart) -> Result<f64, OffsetError> {
+    let Some(value) = chart.song().property("Offset") else {
+        return Ok(0.0);
+    };
+    let seconds: f64 = value
+        .trim_matches('"')
+        .trim()
+        .parse()
+        .map_err(|_| OffsetError::InvalidOffset(value.to_string()))?;
+    let delta_ms = seconds * 1000.0;
+    shift_audio(chart, delta_ms)?;
+    chart.song_mut().set_offset(0.0);
+    Ok(delta_ms)
+}
+
+/// Prepend a measure, in the chart's first time signature, whose BPM makes it
+/// last `ms` milliseconds.
+fn insert_lead_in(chart: &mut Chart, ms: f64) -> Result<(), OffsetError> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let measure = (chart
+        .time_signature_map()
+        .measure_length(Tick::ZERO)
+        .round() as u32)
+        .max(1);
+    let bpm = bpm_for(measure, chart.resolution(), ms)?;
+    let signature = chart
+        .synctrack()
+        .events()
+        .iter()
+        .find(|event| {
+            matches!(event, SyncTrackEvent::TimeSignature { .. }) && event.time() == Tick::ZERO
+        })
+        .cloned();
+    chart
+        .insert_time(Tick::ZERO, measure)
+        .map_err(OffsetError::Rescale)?;
+    shift_anchors(chart, Tick::ZERO, ms);
+    let mut lead_in: Vec<SyncTrackEvent> = signature.into_iter().collect();
+    lead_in.push(SyncTrackEvent::Bpm {
+        time: Tick::ZERO,
+        value: bpm,
+    });
+    chart.synctrack_mut().events_mut().splice(0..0, lead_in);
+    Ok(())
+}
+
+/// Speed up the stretch before the first event so it lasts `ms` milliseconds
+/// less, restating the original BPM where it ends.
+fn shorten_lead_in(chart: &mut Chart, ms: f64) -> Result<(), OffsetError> {
+    let first_marker = chart
+        .synctrack()
+        .events()
+        .iter()
+        .map(SyncTrackEvent::time)
+        .filter(|time| *time > Tick::ZERO)
+        .min();
+    let first_event = chart
+        .global_events()
+        .events()
+        .iter()
+        .map(GlobalEvent::time)
+        .chain(
+            chart
+                .tracks()
+                .iter()
+                .flat_map(|track| track.events().iter().map(TrackEvent::time)),
+        )
+        .min();
+    let end = first_marker
+        .into_iter()
+        .chain(first_event)
+        .min()
+        .unwrap_or_default();
+    let tempo = chart.tempo_map();
+    let available_ms = tempo.seconds_at(end) * 1000.0;
+    if available_ms <= ms {
+        return Err(OffsetError::LeadIn {
+            requested_ms: ms,
+            available_ms,
+        });
+    }
+    let bpm = bpm_for(end.get(), chart.resolution(), available_ms - ms)?;
+    let original = tempo.bpm_at(Tick::ZERO);
+
+    let events = chart.synctrack_mut().events_mut();
+    let has_bpm_at = |events: &[SyncTrackEvent], tick: Tick| {
+        events
+            .iter()
+            .any(|event| matches!(event, SyncTrackEvent::Bpm { time, .. } if *time == tick))
+    };
+    if !has_bpm_at(events, end) {
+        insert_marker(events, end, original);
+    }
+    if has_bpm_at(events, Tick::ZERO) {
+        for event in events.iter_mut() {
+            if let SyncTrackEvent::Bpm { time, value } = event {
+                if *time == Tick::ZERO {
+                    *value = bpm;
+                }
+            }
+        }
+    } else {
+        insert_marker(events, Tick::ZERO, bpm);
+    }
+    shift_anchors(chart, end, -ms);
+    Ok(())
+}
+
+/// Insert a BPM marker after the markers that precede it in canonical order.
+fn insert_marker(events: &mut Vec<SyncTrackEvent>, time: Tick, value: u32) {
+    let marker = SyncTrackEvent::Bpm { time, value };
+    let key = (marker.time(), marker.type_rank());
+    let index = events.partition_point(|event| (event.time(), event.type_rank()) <= key);
+    events.insert(index, marker);
+}
+
+/// Move the absolute time of every anchor at or after `from` by `ms`.
+fn shift_anchors(chart: &mut Chart, from: Tick, ms: f64) {
+    for event in chart.synctrack_mut().events_mut() {
+        if let SyncTrackEvent::Anchor { time, value } = event {
+            if *time >= from {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let shifted = (f64::from(*value) + ms * 1000.0)
+                    .round()
+                    .clamp(0.0, f64::from(u32::MAX)) as u32;
+                *value = shifted;
+            }
+        }
+    }
+}
+
+/// BPM, in thousandths of a beat per minute, at which `ticks` ticks last `ms`
+/// milliseconds.
+fn bpm_for(ticks: u32, resolution: u32, ms: f64) -> Result<u32, OffsetError> {
+    let bpm = (60_000_000.0 * f64::from(ticks) / (f64::from(resolution) * ms)).round();
+    if (1.0..=f64::from(u32::MAX)).contains(&bpm) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Ok(bpm as u32)
+    } else {
+        Err(OffsetError::Tempo { ms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const CHART: &str = "[Song]
+{
+  Resolution = 192
+  Offset = 0.5
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+  768 = B 140000
+}
+[Events]
+{
+  768 = E \"section Verse\"
+}
+[ExpertSingle]
+{
+  768 = N 0 0
+  960 = N 1 0
+}
+";
+
+    fn note_seconds(chart: &Chart) -> Vec<f64> {
+        let tempo = chart.tempo_map();
+        chart.tracks()[0]
+            .events()
+            .iter()
+            .map(|event| tempo.seconds_at(event.time()))
+            .collect()
+    }
+
+    #[test]
+    fn test_bake_offset() {
+        let mut chart = Chart::parse(CHART).unwrap().1;
+        assert!((chart.bake_offset().unwrap() - 500.0).abs() < 1e-9);
+        let written = chart.to_string();
+        assert!(written.contains("  Offset = 0\n"));
+        assert!(written.contains("  0 = TS 4\n  0 = B 480000\n  768 = TS 4\n  768 = B 120000\n"));
+        let seconds = note_seconds(&chart);
+        assert!((seconds[0] - 2.5).abs() < 1e-6);
+        assert!((seconds[1] - (2.5 + 60.0 / 140.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_shorten_lead_in() {
+        let mut chart = Chart::parse(CHART).unwrap().1;
+        chart.shift_audio(-1000.0).unwrap();
+        let written = chart.to_string();
+        assert!(written.contains("  0 = TS 4\n  0 = B 240000\n  768 = B 140000\n"));
+        assert!((note_seconds(&chart)[0] - 1.0).abs() < 1e-6);
+
+        let before = chart.clone();
+        let error = chart.shift_audio(-1000.0).unwrap_err();
+        assert!(matches!(error, OffsetError::LeadIn { .. }));
+        assert_eq!(chart, before);
+    }
+
+    #[test]
+    fn test_shorten_lead_in_restates_bpm() {
+        let input = CHART.replace("768 = N 0 0", "384 = N 0 0");
+        let mut chart = Chart::parse(&input).unwrap().1;
+        chart.shift_audio(-500.0).unwrap();
+        let written = chart.to_string();
+        assert!(written.contains("  0 = B 240000\n  384 = B 120000\n  768 = B 140000\n"));
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt::Display;
+
+use crate::{
+    chart::Chart, global_event::GlobalEvent, normalize::normalize_events, tick::Tick,
+    time_signature::TimeSignatureMap, track_event::TrackEvent,
+};
+
+/// An event start or end moved by [`Chart::quantize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MovedEvent {
+    /// Name of the section, without brackets.
+    pub section: String,
+    pub from: Tick,
+    pub to: Tick,
+    /// Whether the end of a sustain or phrase moved rather than its start.
+    pub end: bool,
+}
+
+/// Events removed by [`Chart::quantize`] from a section, as snapping made
+/// them exact copies of others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemovedDuplicates {
+    /// Name of the section, without brackets.
+    pub section: String,
+    pub count: usize,
+}
+
+/// Summary of [`Chart::quantize`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuantizeReport {
+    pub moved: Vec<MovedEvent>,
+    pub duplicates: Vec<RemovedDuplicates>,
+}
+
+struct Snapper<'a> {
+    grid: &'a TimeSignatureMap,
+    subdivision: u32,
+    tolerance: u32,
+}
+
+impl Snapper<'_> {
+    fn snap(&self, tick: Tick) -> Tick {
+        let snapped = self.grid.snap(tick, self.subdivision);
+        if snapped.distance(tick) <= self.tolerance {
+            snapped
+        } else {
+            tick
+        }
+    }
+
+    /// Snap an event starting at `time` with the given length, returning the
+    /// new time and length.
+    fn snap_span(
+        &self,
+        section: &str,
+        time: Tick,
+        length: u32,
+        report: &mut QuantizeReport,
+    ) -> (Tick, u32) {
+        let new_time = self.snap(time);
+        report.record(section, time, new_time, false);
+        if length == 0 {
+            return (new_time, 0);
+        }
+        let end = time.saturating_add(length);
+        let mut new_end = self.snap(end);
+        if new_end <= new_time {
+            new_end = new_time.saturating_add(length);
+        }
+        report.record(section, end, new_end, true);
+        (new_time, new_end.distance(new_time))
+    }
+}
+
+impl QuantizeReport {
+    fn record(&mut self, section: &str, from: Tick, to: Tick, end: bool) {
+        if from != to {
+            self.moved.push(MovedEvent {
+                section: section.to_string(),
+                from,
+                to,
+                end,
+            });
+        }
+    }
+
+    fn record_duplicates(&mut self, section: &str, (_, count): (usize, usize)) {
+        if count > 0 {
+            self.duplicates.push(RemovedDuplicates {
+                section: section.to_string(),
+                count,
+            });
+        }
+    }
+}
+
+/// Snap notes, sustain ends, `S` phrases and lyric events that lie within
+/// `tolerance` ticks of a `1/subdivision` grid point onto it. Sections with
+/// moved events are sorted again, dropping the exact duplicates this creates.
+pub(crate) fn quantize(chart: &mut Chart, subdivision: u32, tolerance: u32) -> QuantizeReport {
+    let grid = chart.time_signature_map();
+    let snapper = Snapper {
+        grid: &grid,
+        subdivision,
+        tolerance,
+    };
+    let mut report = QuantizeReport::default();
+    let global_events = chart.global_events_mut().events_mut();
+    for event in global_events.iter_mut() {
+        if let GlobalEvent::PhraseStart { time }
+        | GlobalEvent::PhraseEnd { time }
+        | GlobalEvent::Lyric { time, .. } = event
+        {
+            let new_time = snapper.snap(*time);
+            report.record("Events", *time, new_time, false);
+            *time = new_time;
+        }
+    }
+    if !report.moved.is_empty() {
+        report.record_duplicates("Events", normalize_events(global_events, GlobalEvent::time));
+    }
+    for track in chart.tracks_mut() {
+        let name = track.name().to_string();
+        let moved_before = report.moved.len();
+        for event in track.events_mut() {
+            match event {
+                TrackEvent::Note {
+                    time,
+                    sustain: length,
+                    ..
+                }
+                | TrackEvent::Special {
+                    time,
+                    content: length,
+                    ..
+                } => {
+                    (*time, *length) = snapper.snap_span(&name, *time, *length, &mut report);
+                }
+                TrackEvent::Event { .. } => {}
+            }
+        }
+        if report.moved.len() > moved_before {
+            let changes = normalize_events(track.events_mut(), |event| {
+                (event.time(), event.type_rank())
+            });
+            report.record_duplicates(&name, changes);
+        }
+    }
+    report
+}
+
+impl Display for QuantizeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for moved in &self.moved {
+            let part = if moved.end { "end" } else { "event" };
+            writeln!(
+                f,
+                "[{}]: {part} moved from tick {} to {}",
+                moved.section, moved.from, moved.to
+            )?;
+        }
+        for duplicates in &self.duplicates {
+            writeln!(
+                f,
+                "[{}]: {} duplicates removed",
+                duplicates.section, duplicates.count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_quantize() {
+        let mut chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+}
+[Events]
+{
+  190 = E \"lyric Hel-\"
+  260 = E \"lyric lo\"
+}
+[ExpertSingle]
+{
+  3 = N 0 187
+  100 = N 1 0
+  190 = N 2 0
+  191 = E solo
+  193 = N 2 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        let report = chart.quantize(16, 3);
+        assert_eq!(report.moved.len(), 5);
+        assert_eq!(
+            report.duplicates,
+            vec![RemovedDuplicates {
+                section: "ExpertSingle".to_string(),
+                count: 1,
+            }]
+        );
+        let written = chart.to_string();
+        assert!(written.contains("  192 = E \"lyric Hel-\""));
+        assert!(written.contains("  260 = E \"lyric lo\""));
+        assert!(written.contains("  0 = N 0 192"));
+        // the snapped note now follows the text event, and only once
+        assert!(written.contains("  100 = N 1 0\n  191 = E solo\n  192 = N 2 0\n}"));
+    }
+}
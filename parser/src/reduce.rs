@@ -0,0 +1,402 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use crate::{
+    chart::Chart,
+    instrument::{parse_track_name, track_name, Difficulty},
+    tick::Tick,
+    time_signature::TimeSignatureMap,
+    track::Track,
+    track_event::TrackEvent,
+};
+
+/// `N` value of an open note on a five-fret track.
+const OPEN: u32 = 7;
+
+/// `S` value of a star power phrase.
+const STAR_POWER: u32 = 2;
+
+/// Grids from strongest to weakest beat, as parts of a whole note.
+const BEAT_STRENGTHS: [u32; 10] = [1, 2, 4, 8, 12, 16, 24, 32, 48, 64];
+
+/// How far one difficulty is thinned out by [`Chart::reduce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReductionLevel {
+    /// Chords closer together than a `1/subdivision` note are thinned out,
+    /// keeping the ones on the strongest beats.
+    pub subdivision: u32,
+    /// Largest number of frets in a chord.
+    pub max_chord: usize,
+    /// Highest fret used, counting green as 0. Chords reaching past it are
+    /// shifted down.
+    pub highest_fret: u32,
+}
+
+/// Density targets of the tracks generated by [`Chart::reduce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReduceOptions {
+    pub hard: ReductionLevel,
+    pub medium: ReductionLevel,
+    pub easy: ReductionLevel,
+}
+
+impl Default for ReduceOptions {
+    fn default() -> Self {
+        Self {
+            hard: ReductionLevel {
+                subdivision: 8,
+                max_chord: 3,
+                highest_fret: 4,
+            },
+            medium: ReductionLevel {
+                subdivision: 4,
+                max_chord: 2,
+                highest_fret: 3,
+            },
+            easy: ReductionLevel {
+                subdivision: 2,
+                max_chord: 1,
+                highest_fret: 2,
+            },
+        }
+    }
+}
+
+/// A track written by [`Chart::reduce`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReducedTrack {
+    pub name: String,
+    pub chords: usize,
+}
+
+/// Summary of [`Chart::reduce`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReduceReport {
+    pub source: String,
+    pub chords: usize,
+    pub tracks: Vec<ReducedTrack>,
+}
+
+/// Error returned by [`Chart::reduce`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReduceError {
+    /// The chart has no track with the given name.
+    MissingTrack(String),
+    /// The track is not an Expert five-fret track.
+    NotExpertFiveFret(String),
+}
+
+impl Display for ReduceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReduceError::MissingTrack(name) => write!(f, "no [{name}] track to reduce"),
+            ReduceError::NotExpertFiveFret(name) => {
+                write!(f, "[{name}] is not an Expert five-fret track")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReduceError {}
+
+pub(crate) fn reduce(
+    chart: &mut Chart,
+    name: &str,
+    options: &ReduceOptions,
+) -> Result<ReduceReport, ReduceError> {
+    let instrument = match parse_track_name(name) {
+        Some((Difficulty::Expert, instrument)) if instrument.is_five_fret() => instrument,
+        _ => return Err(ReduceError::NotExpertFiveFret(name.to_string())),
+    };
+    let track = chart
+        .tracks()
+        .iter()
+        .find(|track| track.name() == name)
+        .ok_or_else(|| ReduceError::MissingTrack(name.to_string()))?;
+
+    // forced and tap modifiers are dropped, star power and solo markers kept
+    let mut chords: BTreeMap<Tick, Vec<(u32, u32)>> = BTreeMap::new();
+    let mut carried = vec![];
+    for event in track.events() {
+        match event {
+            TrackEvent::Note {
+                time,
+                fret,
+                sustain,
+            } if instrument.is_gem(*fret) => {
+                chords.entry(*time).or_default().push((*fret, *sustain));
+            }
+            TrackEvent::Special { kind, .. } if *kind == STAR_POWER => {
+                carried.push(event.clone());
+            }
+            TrackEvent::Event { .. } => carried.push(event.clone()),
+            _ => {}
+        }
+    }
+
+    let grid = chart.time_signature_map();
+    let resolution = chart.resolution();
+    let mut report = ReduceReport {
+        source: name.to_string(),
+        chords: chords.len(),
+        tracks: vec![],
+    };
+    for (difficulty, level) in [
+        (Difficulty::Hard, options.hard),
+        (Difficulty::Medium, options.medium),
+        (Difficulty::Easy, options.easy),
+    ] {
+        let kept = thin(&grid, resolution, chords.keys().copied(), level.subdivision);
+        let mut events: Vec<TrackEvent> = kept
+            .iter()
+            .flat_map(|time| {
+                simplify(&chords[time], level)
+                    .into_iter()
+                    .map(|(fret, sustain)| TrackEvent::Note {
+                        time: *time,
+                        fret,
+                        sustain,
+                    })
+            })
+            .collect();
+        events.extend(carried.iter().cloned());
+        events.sort_by_key(|event| (event.time(), event.type_rank()));
+
+        let name = track_name(difficulty, instrument);
+        report.tracks.push(ReducedTrack {
+            name: name.clone(),
+            chords: kept.len(),
+        });
+        let track = Track::new(name, events);
+        let tracks = chart.tracks_mut();
+        match tracks
+            .iter_mut()
+            .find(|existing| existing.name() == track.name())
+        {
+            Some(existing) => *existing = track,
+            None => tracks.push(track),
+        }
+    }
+    Ok(report)
+}
+
+/// Index into [`BEAT_STRENGTHS`] of the coarsest grid the tick lies on, lower
+/// being stronger.
+fn beat_strength(grid: &TimeSignatureMap, tick: Tick) -> usize {
+    BEAT_STRENGTHS
+        .iter()
+        .position(|subdivision| grid.snap(tick, *subdivision) == tick)
+        .unwrap_or(BEAT_STRENGTHS.len())
+}
+
+/// Keep chords at least a `1/subdivision` note apart, choosing those on the
+/// strongest beats first so that syncopated chords survive where nothing
+/// stronger is near them.
+fn thin(
+    grid: &TimeSignatureMap,
+    resolution: u32,
+    ticks: impl Iterator<Item = Tick>,
+    subdivision: u32,
+) -> BTreeSet<Tick> {
+    let spacing = f64::from(resolution) * 4.0 / f64::from(subdivision.max(1));
+    let mut candidates: Vec<Tick> = ticks.collect();
+    candidates.sort_by_key(|tick| (beat_strength(grid, *tick), *tick));
+    let mut kept = BTreeSet::new();
+    for tick in candidates {
+        let clear = |other: Option<&Tick>| {
+            other.is_none_or(|other| f64::from(other.distance(tick)) >= spacing.floor())
+        };
+        if clear(kept.range(..tick).next_back()) && clear(kept.range(tick..).next()) {
+            kept.insert(tick);
+        }
+    }
+    kept
+}
+
+/// Cut a chord down to the frets allowed by `level`, keeping its outer frets
+/// so the hand movement stays recognisable.
+fn simplify(chord: &[(u32, u32)], level: ReductionLevel) -> Vec<(u32, u32)> {
+    if let Some(open) = chord.iter().find(|(fret, _)| *fret == OPEN) {
+        return vec![*open];
+    }
+    let mut chord = chord.to_vec();
+    chord.sort_unstable();
+    chord.dedup_by_key(|(fret, _)| *fret);
+    let size = level.max_chord.max(1);
+    if chord.len() > size {
+        chord = if size == 1 {
+            vec![chord[0]]
+        } else {
+            (0..size)
+                .map(|index| chord[index * (chord.len() - 1) / (size - 1)])
+                .collect()
+        };
+    }
+    let shift = chord
+        .last()
+        .map_or(0, |(fret, _)| fret.saturating_sub(level.highest_fret));
+    for (fret, _) in &mut chord {
+        *fret = fret.saturating_sub(shift);
+    }
+    chord.dedup_by_key(|(fret, _)| *fret);
+    chord
+}
+
+impl Display for ReduceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for track in &self.tracks {
+            writeln!(
+                f,
+                "[{}]: {} of {} chords from [{}]",
+                track.name, track.chords, self.chords, self.source
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_reduce() {
+        let mut chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  0 = N 2 0
+  0 = N 4 0
+  0 = S 2 768
+  48 = N 3 0
+  48 = N 5 0
+  96 = N 1 0
+  96 = E solo
+  144 = N 2 0
+  192 = N 4 96
+  288 = N 7 0
+  480 = N 2 0
+  480 = N 3 0
+  480 = E soloend
+}
+[EasySingle]
+{
+  0 = N 1 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        let report = chart
+            .reduce("ExpertSingle", &ReduceOptions::default())
+            .unwrap();
+        assert_eq!(
+            report.tracks,
+            vec![
+                ReducedTrack {
+                    name: "HardSingle".to_string(),
+                    chords: 5,
+                },
+                ReducedTrack {
+                    name: "MediumSingle".to_string(),
+                    chords: 3,
+                },
+                ReducedTrack {
+                    name: "EasySingle".to_string(),
+                    chords: 2,
+                },
+            ]
+        );
+        let written = chart.to_string();
+        assert!(written.contains(
+            "[HardSingle]
+{
+  0 = N 0 0
+  0 = N 2 0
+  0 = N 4 0
+  0 = S 2 768
+  96 = N 1 0
+  96 = E solo
+  192 = N 4 96
+  288 = N 7 0
+  480 = N 2 0
+  480 = N 3 0
+  480 = E soloend
+}"
+        ));
+        assert!(written.contains(
+            "[MediumSingle]
+{
+  0 = N 0 0
+  0 = N 3 0
+  0 = S 2 768
+  96 = E solo
+  192 = N 3 96
+  480 = N 2 0
+  480 = N 3 0
+  480 = E soloend
+}"
+        ));
+        assert!(written.contains(
+            "[EasySingle]
+{
+  0 = N 0 0
+  0 = S 2 768
+  96 = E solo
+  480 = N 2 0
+  480 = E soloend
+}"
+        ));
+    }
+
+    #[test]
+    fn test_reduce_errors() {
+        let mut chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[HardSingle]
+{
+  0 = N 0 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        assert_eq!(
+            chart.reduce("HardSingle", &ReduceOptions::default()),
+            Err(ReduceError::NotExpertFiveFret("HardSingle".to_string()))
+        );
+        assert_eq!(
+            chart.reduce("ExpertDoubleBass", &ReduceOptions::default()),
+            Err(ReduceError::MissingTrack("ExpertDoubleBass".to_string()))
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{
+    chart::Chart,
+    instrument::{parse_track_name, Instrument},
+    track_event::TrackEvent,
+};
+
+/// Drum `N` values that sit a fixed distance above the pad they modify.
+const ACCENT: u32 = 33;
+const GHOST: u32 = 39;
+const CYMBAL: u32 = 64;
+
+/// A rearrangement of lanes applied by [`Chart::remap_lanes`]. Lanes are
+/// numbered by their `N` value: 0 (green) to 4 (orange) on five-fret tracks
+/// and 1 (red) to 4 or 5 (green) on drum tracks. Open notes and kicks are
+/// never moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaneRemap {
+    /// Reverse the lanes, so green and orange swap places for a lefty flip.
+    Mirror,
+    /// Move every note this many lanes up, or down if negative. Notes pushed
+    /// past the outermost lane stay on it.
+    Shift(i32),
+    /// Exchange two lanes.
+    Swap(u32, u32),
+}
+
+/// Error returned by [`Chart::remap_lanes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemapError {
+    /// The chart has no track with the given name.
+    MissingTrack(String),
+    /// The track is neither a five-fret nor a drum track.
+    UnsupportedTrack(String),
+    /// A lane to swap does not exist on the track.
+    NoSuchLane(u32),
+}
+
+impl Display for RemapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemapError::MissingTrack(name) => write!(f, "no [{name}] track to remap"),
+            RemapError::UnsupportedTrack(name) => {
+                write!(f, "[{name}] is neither a five-fret nor a drum track")
+            }
+            RemapError::NoSuchLane(lane) => write!(f, "no lane {lane} to swap"),
+        }
+    }
+}
+
+impl std::error::Error for RemapError {}
+
+/// Lanes of a track, inclusive.
+#[derive(Clone, Copy)]
+struct Lanes {
+    first: u32,
+    last: u32,
+}
+
+impl Lanes {
+    fn contains(self, lane: u32) -> bool {
+        (self.first..=self.last).contains(&lane)
+    }
+
+    fn map(self, remap: LaneRemap, lane: u32) -> u32 {
+        match remap {
+            LaneRemap::Mirror => self.first + self.last - lane,
+            LaneRemap::Shift(lanes) => {
+                let shifted = i64::from(lane) + i64::from(lanes);
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let shifted = shifted.clamp(i64::from(self.first), i64::from(self.last)) as u32;
+                shifted
+            }
+            LaneRemap::Swap(a, b) if lane == a => b,
+            LaneRemap::Swap(a, b) if lane == b => a,
+            LaneRemap::Swap(..) => lane,
+        }
+    }
+}
+
+/// The new `N` value of a drum note, or `None` for a cymbal marker on a pad
+/// that cannot be a cymbal.
+fn remap_drum(lanes: Lanes, remap: LaneRemap, fret: u32) -> Option<u32> {
+    let modifier = |base: u32| {
+        fret.checked_sub(base)
+            .filter(|pad| lanes.contains(*pad))
+            .map(|pad| base + lanes.map(remap, pad))
+    };
+    if lanes.contains(fret) {
+        Some(lanes.map(remap, fret))
+    } else if let Some(fret) = modifier(ACCENT).or_else(|| modifier(GHOST)) {
+        Some(fret)
+    } else if let Some(fret) = modifier(CYMBAL) {
+        (CYMBAL + 2..=CYMBAL + 4).contains(&fret).then_some(fret)
+    } else {
+        Some(fret)
+    }
+}
+
+pub(crate) fn remap_lanes(
+    chart: &mut Chart,
+    name: &str,
+    remap: LaneRemap,
+) -> Result<usize, RemapError> {
+    let instrument = parse_track_name(name)
+        .map(|(_, instrument)| instrument)
+        .filter(|instrument| instrument.is_five_fret() || *instrument == Instrument::Drums)
+        .ok_or_else(|| RemapError::UnsupportedTrack(name.to_string()))?;
+    let track = chart
+        .tracks_mut()
+        .iter_mut()
+        .find(|track| track.name() == name)
+        .ok_or_else(|| RemapError::MissingTrack(name.to_string()))?;
+    let drums = instrument == Instrument::Drums;
+    let lanes = if !drums {
+        Lanes { first: 0, last: 4 }
+    } else if track.events().iter().any(|event| {
+        matches!(event, TrackEvent::Note { fret, .. }
+            if [5, ACCENT + 5, GHOST + 5].contains(fret))
+    }) {
+        Lanes { first: 1, last: 5 }
+    } else {
+        Lanes { first: 1, last: 4 }
+    };
+    if let LaneRemap::Swap(a, b) = remap {
+        if let Some(lane) = [a, b].into_iter().find(|lane| !lanes.contains(*lane)) {
+            return Err(RemapError::NoSuchLane(lane));
+        }
+    }
+
+    let mut moved = 0;
+    let mut seen = HashSet::new();
+    track.events_mut().retain_mut(|event| {
+        let TrackEvent::Note { time, fret, .. } = event else {
+            return true;
+        };
+        let new_fret = if drums {
+            remap_drum(lanes, remap, *fret)
+        } else if lanes.contains(*fret) {
+            Some(lanes.map(remap, *fret))
+        } else {
+            Some(*fret)
+        };
+        let Some(new_fret) = new_fret else {
+            return false;
+        };
+        if new_fret != *fret {
+            moved += 1;
+            *fret = new_fret;
+        }
+        // shifting can push two notes of a chord onto the same lane
+        seen.insert((*time, new_fret))
+    });
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const CHART: &str = "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  0 = N 1 0
+  0 = N 5 0
+  192 = N 7 96
+  384 = N 4 0
+}
+[ExpertDrums]
+{
+  0 = N 0 0
+  0 = N 2 0
+  0 = N 66 0
+  0 = N 3 0
+  192 = N 1 0
+  192 = N 34 0
+  384 = N 4 0
+  384 = N 68 0
+}
+";
+
+    fn track(chart: &Chart, name: &str) -> String {
+        chart
+            .tracks()
+            .iter()
+            .find(|track| track.name() == name)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_remap_five_fret() {
+        let mut chart = Chart::parse(CHART).unwrap().1;
+        assert_eq!(chart.remap_lanes("ExpertSingle", LaneRemap::Mirror), Ok(3));
+        assert_eq!(
+            track(&chart, "ExpertSingle"),
+            "[ExpertSingle]\n{\n  0 = N 4 0\n  0 = N 3 0\n  0 = N 5 0\n  192 = N 7 96\n  384 = N 0 0\n}\n"
+        );
+        assert_eq!(
+            chart.remap_lanes("ExpertSingle", LaneRemap::Shift(1)),
+            Ok(2)
+        );
+        assert_eq!(
+            track(&chart, "ExpertSingle"),
+            "[ExpertSingle]\n{\n  0 = N 4 0\n  0 = N 5 0\n  192 = N 7 96\n  384 = N 1 0\n}\n"
+        );
+        assert_eq!(
+            chart.remap_lanes("ExpertSingle", LaneRemap::Swap(0, 5)),
+            Err(RemapError::NoSuchLane(5))
+        );
+        assert_eq!(
+            chart.remap_lanes("ExpertGHLGuitar", LaneRemap::Mirror),
+            Err(RemapError::UnsupportedTrack("ExpertGHLGuitar".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_remap_drums() {
+        let mut chart = Chart::parse(CHART).unwrap().1;
+        assert_eq!(
+            chart.remap_lanes("ExpertDrums", LaneRemap::Swap(2, 3)),
+            Ok(3)
+        );
+        assert_eq!(
+            track(&chart, "ExpertDrums"),
+            "[ExpertDrums]\n{\n  0 = N 0 0\n  0 = N 3 0\n  0 = N 67 0\n  0 = N 2 0\n  192 = N 1 0\n  192 = N 34 0\n  384 = N 4 0\n  384 = N 68 0\n}\n"
+        );
+        // the green cymbal marker is dropped, as red cannot be a cymbal
+        assert_eq!(chart.remap_lanes("ExpertDrums", LaneRemap::Mirror), Ok(6));
+        assert_eq!(
+            track(&chart, "ExpertDrums"),
+            "[ExpertDrums]\n{\n  0 = N 0 0\n  0 = N 2 0\n  0 = N 66 0\n  0 = N 3 0\n  192 = N 4 0\n  192 = N 37 0\n  384 = N 1 0\n}\n"
+        );
+    }
+}
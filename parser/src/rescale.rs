@@ -0,0 +1,200 @@
+use std::fmt::Display;
+
+use crate::tick::Tick;
+
+/// Maps ticks of a chart to their rescaled values, keeping events that are
+/// close together at their original distance.
+///
+/// Every tick an event starts on is mapped up front, in order. A tick within
+/// `threshold` of the previous one keeps its distance to it, any other tick is
+/// multiplied by `numerator / denominator`, rounding down. Because the whole chart shares one map, events on
+/// the same tick in different sections stay together. Ticks that would
+/// overflow map to `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TickMap {
+    numerator: u32,
+    denominator: u32,
+    threshold: u32,
+    ticks: Vec<Tick>,
+    mapped: Vec<Option<Tick>>,
+}
+
+impl TickMap {
+    pub(crate) fn new(
+        ticks: impl IntoIterator<Item = Tick>,
+        numerator: u32,
+        denominator: u32,
+        threshold: u32,
+    ) -> Self {
+        let mut ticks: Vec<Tick> = ticks.into_iter().collect();
+        ticks.sort_unstable();
+        ticks.dedup();
+        let mut mapped = Vec::with_capacity(ticks.len());
+        let mut previous: Option<(Tick, Option<Tick>)> = None;
+        for &tick in &ticks {
+            let new = match previous {
+                Some((prev, prev_new)) if tick.distance(prev) <= threshold => {
+                    prev_new.and_then(|prev_new| prev_new.checked_add(tick.distance(prev)))
+                }
+                _ => scale(tick.get(), numerator, denominator).map(Tick::new),
+            };
+            mapped.push(new);
+            previous = Some((tick, new));
+        }
+        Self {
+            numerator,
+            denominator,
+            threshold,
+            ticks,
+            mapped,
+        }
+    }
+
+    /// New value of a tick an event starts on.
+    pub(crate) fn time(&self, time: Tick) -> Option<Tick> {
+        match self.ticks.binary_search(&time) {
+            Ok(index) => self.mapped[index],
+            Err(_) => scale(time.get(), self.numerator, self.denominator).map(Tick::new),
+        }
+    }
+
+    /// New length of a sustain or phrase starting at `time`. If it ends within
+    /// the threshold of a later event, it keeps its distance to that event.
+    pub(crate) fn length(&self, time: Tick, length: u32) -> Option<u32> {
+        if length == 0 {
+            return Some(0);
+        }
+        let end = time.checked_add(length)?;
+        let start = self.time(time)?;
+        let index = self.ticks.partition_point(|tick| *tick < end);
+        let nearest = [index.checked_sub(1), Some(index)]
+            .into_iter()
+            .flatten()
+            .filter_map(|index| Some((*self.ticks.get(index)?, self.mapped[index])))
+            .filter(|(tick, _)| *tick > time && tick.distance(end) <= self.threshold)
+            .min_by_key(|(tick, _)| tick.distance(end));
+        let new_length = match nearest {
+            Some((tick, mapped)) => {
+                let mapped = mapped?;
+                let new_end = if end >= tick {
+                    mapped.checked_add(end.distance(tick))?
+                } else {
+                    mapped.checked_sub(end.distance(tick)).unwrap_or(start)
+                };
+                new_end.get().saturating_sub(start.get())
+            }
+            None => scale(length, self.numerator, self.denominator)?,
+        };
+        start.checked_add(new_length)?;
+        Some(new_length)
+    }
+}
+
+/// `value * numerator / denominator` rounded down, or `None` if it does not fit.
+fn scale(value: u32, numerator: u32, denominator: u32) -> Option<u32> {
+    (u64::from(value) * u64::from(numerator))
+        .checked_div(u64::from(denominator))
+        .and_then(|value| u32::try_from(value).ok())
+}
+
+/// Greatest common divisor, with `gcd(0, x) == x`.
+pub(crate) fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, failing like rescaling `b` up to it would.
+pub(crate) fn lcm(a: u32, b: u32) -> Result<u32, RescaleError> {
+    let factor = a / gcd(a, b).max(1);
+    b.checked_mul(factor).ok_or(RescaleError::Resolution {
+        resolution: b,
+        factor,
+    })
+}
+
+/// Error returned when rescaling or shifting a chart would overflow a tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RescaleError {
+    /// The resolution itself does not fit after rescaling.
+    Resolution { resolution: u32, factor: u32 },
+    /// The first event, in file order, whose time or length does not fit.
+    Event { section: String, time: Tick },
+}
+
+impl RescaleError {
+    pub(crate) fn event(section: &str, time: Tick) -> Self {
+        RescaleError::Event {
+            section: section.to_string(),
+            time,
+        }
+    }
+}
+
+impl Display for RescaleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RescaleError::Resolution { resolution, factor } => {
+                write!(
+                    f,
+                    "resolution {resolution} overflows when multiplied by {factor}"
+                )
+            }
+            RescaleError::Event { section, time } => {
+                write!(
+                    f,
+                    "event at tick {time} in [{section}] overflows when rescaled"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RescaleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_map() {
+        let map = TickMap::new([0, 192, 193, 195, 384].map(Tick::new), 2, 1, 1);
+        assert_eq!(map.time(Tick::new(192)), Some(Tick::new(384)));
+        assert_eq!(map.time(Tick::new(193)), Some(Tick::new(385)));
+        assert_eq!(map.time(Tick::new(195)), Some(Tick::new(390)));
+        assert_eq!(map.time(Tick::new(100)), Some(Tick::new(200)));
+        assert_eq!(map.length(Tick::new(0), 192), Some(384));
+        assert_eq!(map.length(Tick::new(0), 193), Some(385));
+        assert_eq!(map.length(Tick::new(195), 188), Some(377));
+        assert_eq!(map.length(Tick::new(0), 100), Some(200));
+    }
+
+    #[test]
+    fn test_tick_map_divide() {
+        let map = TickMap::new([0, 480, 960].map(Tick::new), 1, 5, 0);
+        assert_eq!(map.time(Tick::new(480)), Some(Tick::new(96)));
+        assert_eq!(map.length(Tick::new(0), 480), Some(96));
+    }
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(480, 960), 480);
+        assert_eq!(gcd(0, 192), 192);
+        assert_eq!(gcd(192, 100), 4);
+    }
+
+    #[test]
+    fn test_tick_map_overflow() {
+        let map = TickMap::new([0, u32::MAX / 2, u32::MAX / 2 + 1].map(Tick::new), 2, 1, 1);
+        assert_eq!(
+            map.time(Tick::new(u32::MAX / 2)),
+            Some(Tick::new(u32::MAX - 1))
+        );
+        assert_eq!(
+            map.time(Tick::new(u32::MAX / 2 + 1)),
+            Some(Tick::new(u32::MAX))
+        );
+        assert_eq!(map.length(Tick::new(0), u32::MAX / 2 + 2), None);
+    }
+}
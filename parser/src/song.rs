@@ -1,15 +1,74 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display, str::FromStr};
 
 use nom::{bytes::complete::tag, combinator::map_res, multi::many1, sequence::preceded, IResult};
 
 use crate::{
     components::{curlied, spaced},
-    song_property::SongProperty,
+    rescale::RescaleError,
+    song_property::{quote, unquote, SongProperty},
 };
 
-#[derive(Debug, PartialEq, Eq)]
-pub(crate) struct Song<'a> {
+/// Audio files a chart can name in its `[Song]` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AudioStream {
+    Music,
+    Guitar,
+    Rhythm,
+    Bass,
+    Drum,
+    Drum2,
+    Drum3,
+    Drum4,
+    Vocal,
+    Keys,
+    Crowd,
+}
+
+impl AudioStream {
+    pub const ALL: [AudioStream; 11] = [
+        AudioStream::Music,
+        AudioStream::Guitar,
+        AudioStream::Rhythm,
+        AudioStream::Bass,
+        AudioStream::Drum,
+        AudioStream::Drum2,
+        AudioStream::Drum3,
+        AudioStream::Drum4,
+        AudioStream::Vocal,
+        AudioStream::Keys,
+        AudioStream::Crowd,
+    ];
+
+    /// Name of the `[Song]` property holding the file name.
+    #[must_use]
+    pub fn key(self) -> &'static str {
+        match self {
+            AudioStream::Music => "MusicStream",
+            AudioStream::Guitar => "GuitarStream",
+            AudioStream::Rhythm => "RhythmStream",
+            AudioStream::Bass => "BassStream",
+            AudioStream::Drum => "DrumStream",
+            AudioStream::Drum2 => "Drum2Stream",
+            AudioStream::Drum3 => "Drum3Stream",
+            AudioStream::Drum4 => "Drum4Stream",
+            AudioStream::Vocal => "VocalStream",
+            AudioStream::Keys => "KeysStream",
+            AudioStream::Crowd => "CrowdStream",
+        }
+    }
+}
+
+impl Display for AudioStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Song<'a> {
     resolution: u32,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     properties: Vec<SongProperty<'a>>,
 }
 
@@ -22,8 +81,195 @@ impl<'a> Song<'a> {
         }
     }
 
-    pub(crate) fn multiply(&mut self, factor: u32) {
-        self.resolution *= factor;
+    pub(crate) fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    pub(crate) fn properties(&self) -> &[SongProperty<'a>] {
+        &self.properties
+    }
+
+    /// Raw value of the property with the given name, quotes included.
+    pub(crate) fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|property| property.name() == name)
+            .map(SongProperty::value)
+    }
+
+    /// Replace the value of the property with the given name, adding it if it
+    /// is missing.
+    pub(crate) fn set_property(&mut self, name: &str, value: impl Into<Cow<'a, str>>) {
+        let value = value.into();
+        match self
+            .properties
+            .iter_mut()
+            .find(|property| property.name() == name)
+        {
+            Some(property) => property.set_value(value),
+            None => self
+                .properties
+                .push(SongProperty::new(name.to_string(), value)),
+        }
+    }
+
+    /// Unquoted value of a text property.
+    fn text(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.property(name)
+            .map(|value| Cow::Borrowed(unquote(value)))
+    }
+
+    fn set_text(&mut self, name: &str, value: &str) {
+        self.set_property(name, quote(value));
+    }
+
+    /// Value of a numeric property, or `None` if it is missing or not a number.
+    fn number<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.text(name)?.trim().parse().ok()
+    }
+
+    fn set_number(&mut self, name: &str, value: impl Display) {
+        self.set_property(name, value.to_string());
+    }
+
+    #[must_use]
+    pub fn name(&self) -> Option<Cow<'_, str>> {
+        self.text("Name")
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.set_text("Name", name);
+    }
+
+    #[must_use]
+    pub fn artist(&self) -> Option<Cow<'_, str>> {
+        self.text("Artist")
+    }
+
+    pub fn set_artist(&mut self, artist: &str) {
+        self.set_text("Artist", artist);
+    }
+
+    #[must_use]
+    pub fn charter(&self) -> Option<Cow<'_, str>> {
+        self.text("Charter")
+    }
+
+    pub fn set_charter(&mut self, charter: &str) {
+        self.set_text("Charter", charter);
+    }
+
+    #[must_use]
+    pub fn album(&self) -> Option<Cow<'_, str>> {
+        self.text("Album")
+    }
+
+    pub fn set_album(&mut self, album: &str) {
+        self.set_text("Album", album);
+    }
+
+    /// Release year, without the `", "` that editors put in front of it.
+    #[must_use]
+    pub fn year(&self) -> Option<Cow<'_, str>> {
+        self.text("Year").map(|year| match year {
+            Cow::Borrowed(year) => Cow::Borrowed(year.trim_start_matches(", ")),
+            Cow::Owned(year) => Cow::Owned(year.trim_start_matches(", ").to_string()),
+        })
+    }
+
+    /// Set the release year, written as `", 2001"` like editors do.
+    pub fn set_year(&mut self, year: &str) {
+        self.set_text("Year", &format!(", {year}"));
+    }
+
+    /// Seconds of audio before tick 0.
+    #[must_use]
+    pub fn offset(&self) -> Option<f64> {
+        self.number("Offset")
+    }
+
+    pub fn set_offset(&mut self, seconds: f64) {
+        self.set_number("Offset", seconds);
+    }
+
+    /// Instrument played by the second player, such as `bass` or `rhythm`.
+    #[must_use]
+    pub fn player2(&self) -> Option<Cow<'_, str>> {
+        self.text("Player2")
+    }
+
+    pub fn set_player2(&mut self, instrument: &str) {
+        self.set_property("Player2", instrument.to_string());
+    }
+
+    #[must_use]
+    pub fn difficulty(&self) -> Option<i32> {
+        self.number("Difficulty")
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: i32) {
+        self.set_number("Difficulty", difficulty);
+    }
+
+    /// Start of the song preview in seconds.
+    #[must_use]
+    pub fn preview_start(&self) -> Option<f64> {
+        self.number("PreviewStart")
+    }
+
+    pub fn set_preview_start(&mut self, seconds: f64) {
+        self.set_number("PreviewStart", seconds);
+    }
+
+    /// End of the song preview in seconds.
+    #[must_use]
+    pub fn preview_end(&self) -> Option<f64> {
+        self.number("PreviewEnd")
+    }
+
+    pub fn set_preview_end(&mut self, seconds: f64) {
+        self.set_number("PreviewEnd", seconds);
+    }
+
+    #[must_use]
+    pub fn genre(&self) -> Option<Cow<'_, str>> {
+        self.text("Genre")
+    }
+
+    pub fn set_genre(&mut self, genre: &str) {
+        self.set_text("Genre", genre);
+    }
+
+    #[must_use]
+    pub fn media_type(&self) -> Option<Cow<'_, str>> {
+        self.text("MediaType")
+    }
+
+    pub fn set_media_type(&mut self, media_type: &str) {
+        self.set_text("MediaType", media_type);
+    }
+
+    /// File name of an audio stream, relative to the song folder.
+    #[must_use]
+    pub fn stream(&self, stream: AudioStream) -> Option<Cow<'_, str>> {
+        self.text(stream.key())
+    }
+
+    pub fn set_stream(&mut self, stream: AudioStream, file: &str) {
+        self.set_text(stream.key(), file);
+    }
+
+    /// Multiply the resolution by `numerator / denominator`.
+    pub(crate) fn rescale(&mut self, numerator: u32, denominator: u32) -> Result<(), RescaleError> {
+        self.resolution =
+            self.resolution
+                .checked_mul(numerator)
+                .ok_or(RescaleError::Resolution {
+                    resolution: self.resolution,
+                    factor: numerator,
+                })?
+                / denominator;
+        Ok(())
     }
 
     #[inline]
@@ -101,4 +347,57 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_typed_properties() {
+        let mut song = Song::parse(
+            r#"[Song]
+{
+  Name = "Second Sight"
+  Year = ", 2001"
+  Offset = 0.25
+  Resolution = 192
+  Player2 = bass
+  Difficulty = 4
+  PreviewStart = 0
+  Genre = "Neoclassical Metal"
+  GuitarStream = "guitar.ogg"
+  Custom = "kept"
+}"#,
+        )
+        .unwrap()
+        .1;
+        assert_eq!(song.name().unwrap(), "Second Sight");
+        assert_eq!(song.year().unwrap(), "2001");
+        assert_eq!(song.offset(), Some(0.25));
+        assert_eq!(song.player2().unwrap(), "bass");
+        assert_eq!(song.difficulty(), Some(4));
+        assert_eq!(song.preview_start(), Some(0.0));
+        assert_eq!(song.preview_end(), None);
+        assert_eq!(song.stream(AudioStream::Guitar).unwrap(), "guitar.ogg");
+        assert_eq!(song.stream(AudioStream::Music), None);
+
+        song.set_name("Say \"Hi\"");
+        song.set_year("2002");
+        song.set_offset(-1.5);
+        song.set_stream(AudioStream::Music, "song.opus");
+        assert_eq!(song.name().unwrap(), "Say \"Hi\"");
+        assert_eq!(
+            song.to_string(),
+            r#"[Song]
+{
+  Resolution = 192
+  Name = "Say "Hi""
+  Year = ", 2002"
+  Offset = -1.5
+  Player2 = bass
+  Difficulty = 4
+  PreviewStart = 0
+  Genre = "Neoclassical Metal"
+  GuitarStream = "guitar.ogg"
+  Custom = "kept"
+  MusicStream = "song.opus"
+}"#
+        );
+    }
 }
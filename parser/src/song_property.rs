@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display};
 
 use nom::{
     bytes::complete::tag,
@@ -8,10 +8,13 @@ use nom::{
     IResult,
 };
 
-#[derive(Debug, PartialEq, Eq)]
-pub(crate) struct SongProperty<'a> {
-    name: &'a str,
-    value: &'a str,
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SongProperty<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    value: Cow<'a, str>,
 }
 
 impl<'a> Display for SongProperty<'a> {
@@ -22,8 +25,11 @@ impl<'a> Display for SongProperty<'a> {
 
 impl<'a> SongProperty<'a> {
     #[must_use]
-    pub(crate) fn new(name: &'a str, value: &'a str) -> Self {
-        Self { name, value }
+    pub(crate) fn new(name: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
     }
 
     #[inline]
@@ -34,11 +40,48 @@ impl<'a> SongProperty<'a> {
         )(input)
     }
 
-    pub(crate) fn name(&self) -> &'a str {
-        self.name
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub(crate) fn set_value(&mut self, value: impl Into<Cow<'a, str>>) {
+        self.value = value.into();
     }
+}
+
+/// Wrap a value in quotes. Nothing inside is escaped, as players show text
+/// exactly as it is written, and .chart files keep Windows paths as they are.
+/// [`Song`](crate::song::Song) values and event text follow the same rule.
+pub(crate) fn quote(value: &str) -> String {
+    format!("\"{value}\"")
+}
+
+/// Strip the quotes around a value, keeping everything inside them verbatim.
+/// Unquoted values are returned as they are.
+pub(crate) fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    pub(crate) fn value(&self) -> &'a str {
-        self.value
+    #[test]
+    fn test_quoting() {
+        for value in ["Song", "", "say \"hi\"", "C:\\Music\\", "a\\\"b"] {
+            assert_eq!(unquote(&quote(value)), value);
+        }
+        assert_eq!(quote("a \"b\""), "\"a \"b\"\"");
+        assert_eq!(unquote("bass"), "bass");
+        assert_eq!(unquote("\""), "\"");
     }
 }
@@ -0,0 +1,246 @@
+use std::{fmt::Display, ops::Range};
+
+use crate::{
+    chart::Chart,
+    instrument::{parse_track_name, track_name, Difficulty, Instrument},
+    tick::Tick,
+    track::Track,
+    track_event::TrackEvent,
+};
+
+/// `S` kind used for star power phrases.
+pub(crate) const STAR_POWER: u32 = 2;
+
+/// Phrases written to one track by [`Chart::copy_star_power`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CopiedPhrases {
+    pub track: String,
+    pub copied: usize,
+    /// Phrases left out because no notes of the track fall inside them.
+    pub skipped: usize,
+}
+
+/// Summary of [`Chart::copy_star_power`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StarPowerReport {
+    pub source: String,
+    pub tracks: Vec<CopiedPhrases>,
+}
+
+/// Error returned by [`Chart::copy_star_power`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StarPowerError {
+    /// The chart has no track with the given name.
+    MissingTrack(String),
+    /// The track name does not name a difficulty and instrument.
+    UnknownTrack(String),
+}
+
+impl Display for StarPowerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StarPowerError::MissingTrack(name) => {
+                write!(f, "no [{name}] track to copy star power from")
+            }
+            StarPowerError::UnknownTrack(name) => write!(f, "unknown track name [{name}]"),
+        }
+    }
+}
+
+impl std::error::Error for StarPowerError {}
+
+/// Sorted start ticks of the playable notes of a track.
+pub(crate) fn gem_times(track: &Track, instrument: Instrument) -> Vec<Tick> {
+    let mut times: Vec<Tick> = track
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            TrackEvent::Note { time, fret, .. } if instrument.is_gem(*fret) => Some(*time),
+            _ => None,
+        })
+        .collect();
+    times.sort_unstable();
+    times.dedup();
+    times
+}
+
+/// Ticks covered by the star power phrases of a track. A phrase of length 0
+/// still covers its own tick.
+pub(crate) fn phrases(track: &Track) -> Vec<Range<Tick>> {
+    track
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            TrackEvent::Special {
+                time,
+                kind: STAR_POWER,
+                content,
+            } => Some(*time..time.saturating_add((*content).max(1))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The notes of `times` inside `range`.
+pub(crate) fn notes_in(times: &[Tick], range: &Range<Tick>) -> Vec<Tick> {
+    let start = times.partition_point(|time| *time < range.start);
+    times[start..]
+        .iter()
+        .take_while(|time| **time < range.end)
+        .copied()
+        .collect()
+}
+
+pub(crate) fn copy_star_power(
+    chart: &mut Chart,
+    name: &str,
+) -> Result<StarPowerReport, StarPowerError> {
+    let (difficulty, instrument) =
+        parse_track_name(name).ok_or_else(|| StarPowerError::UnknownTrack(name.to_string()))?;
+    let source = chart
+        .tracks()
+        .iter()
+        .find(|track| track.name() == name)
+        .ok_or_else(|| StarPowerError::MissingTrack(name.to_string()))?;
+    let source_phrases = phrases(source);
+
+    let mut report = StarPowerReport {
+        source: name.to_string(),
+        tracks: vec![],
+    };
+    let siblings: Vec<String> = Difficulty::ALL
+        .into_iter()
+        .filter(|other| *other != difficulty)
+        .map(|other| track_name(other, instrument))
+        .collect();
+    for track in chart.tracks_mut() {
+        if !siblings.iter().any(|sibling| sibling == track.name()) {
+            continue;
+        }
+        let times = gem_times(track, instrument);
+        let mut copied = CopiedPhrases {
+            track: track.name().to_string(),
+            copied: 0,
+            skipped: 0,
+        };
+        let events = track.events_mut();
+        events.retain(|event| {
+            !matches!(
+                event,
+                TrackEvent::Special {
+                    kind: STAR_POWER,
+                    ..
+                }
+            )
+        });
+        for phrase in &source_phrases {
+            // the phrase runs from the first to just past the last note it
+            // holds on this track
+            let notes = notes_in(&times, phrase);
+            match (notes.first(), notes.last()) {
+                (Some(first), Some(last)) => {
+                    events.push(TrackEvent::Special {
+                        time: *first,
+                        kind: STAR_POWER,
+                        content: last.distance(*first) + 1,
+                    });
+                    copied.copied += 1;
+                }
+                _ => copied.skipped += 1,
+            }
+        }
+        events.sort_by_key(|event| (event.time(), event.type_rank()));
+        report.tracks.push(copied);
+    }
+    Ok(report)
+}
+
+impl Display for StarPowerReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for track in &self.tracks {
+            writeln!(
+                f,
+                "[{}]: copied {} star power phrases from [{}], skipped {} without notes",
+                track.track, track.copied, self.source, track.skipped
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_copy_star_power() {
+        let mut chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  0 = S 2 384
+  96 = N 1 0
+  192 = N 2 0
+  768 = S 2 192
+  768 = N 3 0
+}
+[HardSingle]
+{
+  96 = N 1 0
+  192 = N 2 0
+  200 = S 2 10
+}
+[EasySingle]
+{
+  192 = N 0 0
+  768 = N 1 0
+}
+[ExpertDrums]
+{
+  0 = N 1 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        let report = chart.copy_star_power("ExpertSingle").unwrap();
+        assert_eq!(
+            report.tracks,
+            vec![
+                CopiedPhrases {
+                    track: "HardSingle".to_string(),
+                    copied: 1,
+                    skipped: 1,
+                },
+                CopiedPhrases {
+                    track: "EasySingle".to_string(),
+                    copied: 2,
+                    skipped: 0,
+                },
+            ]
+        );
+        let written = chart.to_string();
+        assert!(written.contains(
+            "[HardSingle]\n{\n  96 = N 1 0\n  96 = S 2 97\n  192 = N 2 0\n}\n[EasySingle]\n{\n  192 = N 0 0\n  192 = S 2 1\n  768 = N 1 0\n  768 = S 2 1\n}\n"
+        ));
+        assert_eq!(
+            chart.copy_star_power("ExpertGuitar"),
+            Err(StarPowerError::UnknownTrack("ExpertGuitar".to_string()))
+        );
+    }
+}
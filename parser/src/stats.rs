@@ -0,0 +1,229 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+    chart::Chart,
+    global_event::GlobalEvent,
+    instrument::{parse_track_name, Instrument},
+    sync_track_event::SyncTrackEvent,
+    tick::Tick,
+    track::Track,
+    track_event::TrackEvent,
+};
+
+/// `S` kind used for star power phrases.
+const STAR_POWER: u32 = 2;
+
+/// Summary of a whole chart, as returned by [`Chart::stats`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartStats {
+    pub resolution: u32,
+    /// Smallest resolution that still represents every event exactly.
+    pub lowest_lossless_resolution: u32,
+    /// Time of the last event, including sustains and phrase lengths.
+    pub length_seconds: f64,
+    /// Lowest BPM used, `None` if the chart has no BPM markers.
+    pub min_bpm: Option<f64>,
+    /// Highest BPM used, `None` if the chart has no BPM markers.
+    pub max_bpm: Option<f64>,
+    /// Distinct time signatures as (numerator, denominator), in order of first use.
+    pub time_signatures: Vec<(u32, u32)>,
+    pub tracks: Vec<TrackStats>,
+    pub lyric_phrases: usize,
+    pub sections: usize,
+}
+
+/// Note counts for a single track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackStats {
+    pub name: String,
+    /// Number of distinct ticks with at least one gem.
+    pub notes: usize,
+    /// Number of distinct ticks with two or more gems.
+    pub chords: usize,
+    /// Number of distinct ticks with at least one sustained gem.
+    pub sustains: usize,
+    pub star_power_phrases: usize,
+}
+
+impl ChartStats {
+    pub(crate) fn new(chart: &Chart) -> Self {
+        let tempo_map = chart.tempo_map();
+        let bpms = chart
+            .synctrack()
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                SyncTrackEvent::Bpm { value, .. } => Some(f64::from(*value) / 1000.0),
+                _ => None,
+            });
+        let min_bpm = bpms.clone().reduce(f64::min);
+        let max_bpm = bpms.reduce(f64::max);
+        let mut time_signatures = vec![];
+        for event in chart.synctrack().events() {
+            if let SyncTrackEvent::TimeSignature { value1, value2, .. } = event {
+                let signature = (*value1, 1 << value2.unwrap_or(2).min(31));
+                if !time_signatures.contains(&signature) {
+                    time_signatures.push(signature);
+                }
+            }
+        }
+        let global_events = chart.global_events().events();
+        Self {
+            resolution: chart.resolution(),
+            lowest_lossless_resolution: chart.lowest_lossless_resolution(),
+            length_seconds: tempo_map.seconds_at(chart.last_tick()),
+            min_bpm,
+            max_bpm,
+            time_signatures,
+            tracks: chart.tracks().iter().map(TrackStats::new).collect(),
+            lyric_phrases: global_events
+                .iter()
+                .filter(|event| matches!(event, GlobalEvent::PhraseStart { .. }))
+                .count(),
+            sections: global_events
+                .iter()
+                .filter(|event| matches!(event, GlobalEvent::Section { .. }))
+                .count(),
+        }
+    }
+}
+
+impl TrackStats {
+    pub(crate) fn new(track: &Track) -> Self {
+        let instrument = parse_track_name(track.name()).map_or(Instrument::Single, |x| x.1);
+        let mut positions: BTreeMap<Tick, (usize, bool)> = BTreeMap::new();
+        let mut star_power_phrases = 0;
+        for event in track.events() {
+            match event {
+                TrackEvent::Note {
+                    time,
+                    fret,
+                    sustain,
+                } if instrument.is_gem(*fret) => {
+                    let position = positions.entry(*time).or_default();
+                    position.0 += 1;
+                    position.1 |= *sustain > 0;
+                }
+                TrackEvent::Special {
+                    kind: STAR_POWER, ..
+                } => star_power_phrases += 1,
+                _ => {}
+            }
+        }
+        Self {
+            name: track.name().to_string(),
+            notes: positions.len(),
+            chords: positions.values().filter(|(gems, _)| *gems > 1).count(),
+            sustains: positions
+                .values()
+                .filter(|(_, sustained)| *sustained)
+                .count(),
+            star_power_phrases,
+        }
+    }
+}
+
+impl Display for ChartStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Resolution: {}", self.resolution)?;
+        writeln!(
+            f,
+            "Lowest lossless resolution: {}",
+            self.lowest_lossless_resolution
+        )?;
+        let minutes = (self.length_seconds / 60.0).floor();
+        let seconds = self.length_seconds - minutes * 60.0;
+        writeln!(
+            f,
+            "Length: {minutes}:{seconds:06.3} ({:.3}s)",
+            self.length_seconds
+        )?;
+        match (self.min_bpm, self.max_bpm) {
+            (Some(min), Some(max)) => writeln!(f, "BPM: {min} - {max}")?,
+            _ => writeln!(f, "BPM: none")?,
+        }
+        writeln!(
+            f,
+            "Time signatures: {}",
+            self.time_signatures
+                .iter()
+                .map(|(numerator, denominator)| format!("{numerator}/{denominator}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(f, "Sections: {}", self.sections)?;
+        writeln!(f, "Lyric phrases: {}", self.lyric_phrases)?;
+        writeln!(f, "Tracks:")?;
+        for track in &self.tracks {
+            write!(f, "{track}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TrackStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "  {}: {} notes, {} chords, {} sustains, {} star power phrases",
+            self.name, self.notes, self.chords, self.sustains, self.star_power_phrases
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_track_stats() {
+        let track = Track::parse(
+            "[ExpertSingle]
+{
+  0 = N 0 0
+  0 = N 1 0
+  192 = N 2 96
+  192 = N 5 0
+  384 = N 7 0
+  384 = S 2 192
+}",
+        )
+        .unwrap()
+        .1;
+        let stats = TrackStats::new(&track);
+        assert_eq!(stats.notes, 3);
+        assert_eq!(stats.chords, 1);
+        assert_eq!(stats.sustains, 1);
+        assert_eq!(stats.star_power_phrases, 1);
+    }
+
+    #[test]
+    fn test_huge_denominator() {
+        let chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4 32
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+}
+",
+        )
+        .unwrap()
+        .1;
+        assert_eq!(chart.stats().time_signatures, vec![(4, 1 << 31)]);
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt::Display;
+
+use crate::{chart::Chart, track::Track, track_event::TrackEvent};
+
+/// Thresholds used by [`Chart::clean_sustains`], in beats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SustainOptions {
+    /// Sustains shorter than this are removed.
+    pub min_length: f64,
+    /// Sustains are cut short to leave at least this much before the next
+    /// note on any lane.
+    pub gap: f64,
+}
+
+impl Default for SustainOptions {
+    /// Clone Hero's default sustain cutoff of a third of a beat, and a gap of
+    /// a 32nd note.
+    fn default() -> Self {
+        Self {
+            min_length: 1.0 / 3.0,
+            gap: 1.0 / 8.0,
+        }
+    }
+}
+
+/// Changes made to a single track by [`Chart::clean_sustains`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SustainChanges {
+    pub track: String,
+    /// Sustains cut short to leave a gap before the next note.
+    pub trimmed: usize,
+    /// Sustains removed for being too short, including trimmed ones.
+    pub removed: usize,
+}
+
+/// Summary of [`Chart::clean_sustains`], listing only the tracks that changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SustainReport {
+    pub tracks: Vec<SustainChanges>,
+}
+
+pub(crate) fn clean_sustains(chart: &mut Chart, options: &SustainOptions) -> SustainReport {
+    let beat = f64::from(chart.resolution());
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let [min_length, gap] =
+        [options.min_length, options.gap].map(|beats| (beats * beat).round().max(0.0) as u32);
+    let mut report = SustainReport::default();
+    for track in chart.tracks_mut() {
+        let changes = clean_track(track, min_length, gap);
+        if changes.trimmed > 0 || changes.removed > 0 {
+            report.tracks.push(changes);
+        }
+    }
+    report
+}
+
+fn clean_track(track: &mut Track, min_length: u32, gap: u32) -> SustainChanges {
+    let mut starts: Vec<_> = track
+        .events()
+        .iter()
+        .filter(|event| matches!(event, TrackEvent::Note { .. }))
+        .map(TrackEvent::time)
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+    let mut changes = SustainChanges {
+        track: track.name().to_string(),
+        trimmed: 0,
+        removed: 0,
+    };
+    for event in track.events_mut() {
+        let TrackEvent::Note { time, sustain, .. } = event else {
+            continue;
+        };
+        if *sustain == 0 {
+            continue;
+        }
+        let next = starts.get(starts.partition_point(|start| start <= time));
+        if let Some(next) = next {
+            let longest = next.distance(*time).saturating_sub(gap);
+            if *sustain > longest {
+                *sustain = longest;
+                changes.trimmed += 1;
+            }
+        }
+        if *sustain < min_length {
+            *sustain = 0;
+            changes.removed += 1;
+        }
+    }
+    changes
+}
+
+impl Display for SustainReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for changes in &self.tracks {
+            writeln!(
+                f,
+                "[{}]: {} sustains trimmed, {} removed",
+                changes.track, changes.trimmed, changes.removed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_clean_sustains() {
+        let mut chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 40
+  192 = N 1 230
+  192 = N 2 100
+  432 = N 3 150
+  480 = N 4 300
+}
+[EasySingle]
+{
+  0 = N 0 96
+}
+",
+        )
+        .unwrap()
+        .1;
+        let report = chart.clean_sustains(&SustainOptions::default());
+        assert_eq!(
+            report.tracks,
+            vec![SustainChanges {
+                track: "ExpertSingle".to_string(),
+                trimmed: 2,
+                removed: 2,
+            }]
+        );
+        assert!(chart.to_string().contains(
+            "  0 = N 0 0
+  192 = N 1 216
+  192 = N 2 100
+  432 = N 3 0
+  480 = N 4 300
+"
+        ));
+    }
+}
@@ -4,11 +4,14 @@ use nom::{bytes::complete::tag, combinator::map, multi::many1, sequence::precede
 
 use crate::{
     components::{curlied, spaced},
+    rescale::TickMap,
     sync_track_event::SyncTrackEvent,
+    tick::Tick,
 };
 
-#[derive(Debug, PartialEq, Eq)]
-pub(crate) struct SyncTrack {
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncTrack {
     events: Vec<SyncTrackEvent>,
 }
 
@@ -28,10 +31,20 @@ impl SyncTrack {
         )(input)
     }
 
-    pub(crate) fn multiply(&mut self, factor: u32) {
+    pub(crate) fn events(&self) -> &[SyncTrackEvent] {
+        &self.events
+    }
+
+    pub(crate) fn events_mut(&mut self) -> &mut Vec<SyncTrackEvent> {
+        &mut self.events
+    }
+
+    /// Rescale every event, returning the original time of the first one that overflows.
+    pub(crate) fn rescale(&mut self, map: &TickMap) -> Result<(), Tick> {
         for event in &mut self.events {
-            event.multiply(factor);
+            event.rescale(map)?;
         }
+        Ok(())
     }
 }
 
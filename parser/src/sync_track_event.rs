@@ -9,35 +9,64 @@ use nom::{
     IResult,
 };
 
-#[derive(Debug, PartialEq, Eq)]
-pub(crate) enum SyncTrackEvent {
+use crate::{rescale::TickMap, tick::Tick};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SyncTrackEvent {
     Bpm {
-        time: u32,
+        time: Tick,
         value: u32,
     },
     TimeSignature {
-        time: u32,
+        time: Tick,
         value1: u32,
         value2: Option<u32>,
     },
     Anchor {
-        time: u32,
+        time: Tick,
         value: u32,
     },
 }
 
 impl SyncTrackEvent {
-    pub(crate) fn multiply(&mut self, factor: u32) {
+    pub(crate) fn rescale(&mut self, map: &TickMap) -> Result<(), Tick> {
         match self {
             SyncTrackEvent::Bpm { time, .. }
             | SyncTrackEvent::TimeSignature { time, .. }
-            | SyncTrackEvent::Anchor { time, .. } => *time *= factor,
+            | SyncTrackEvent::Anchor { time, .. } => *time = map.time(*time).ok_or(*time)?,
+        }
+        Ok(())
+    }
+
+    pub(crate) fn time(&self) -> Tick {
+        match self {
+            SyncTrackEvent::Bpm { time, .. }
+            | SyncTrackEvent::TimeSignature { time, .. }
+            | SyncTrackEvent::Anchor { time, .. } => *time,
+        }
+    }
+
+    pub(crate) fn time_mut(&mut self) -> &mut Tick {
+        match self {
+            SyncTrackEvent::Bpm { time, .. }
+            | SyncTrackEvent::TimeSignature { time, .. }
+            | SyncTrackEvent::Anchor { time, .. } => time,
+        }
+    }
+
+    /// Order of events sharing a tick in a canonical .chart file.
+    pub(crate) fn type_rank(&self) -> u8 {
+        match self {
+            SyncTrackEvent::TimeSignature { .. } => 0,
+            SyncTrackEvent::Bpm { .. } => 1,
+            SyncTrackEvent::Anchor { .. } => 2,
         }
     }
 
     #[inline]
     pub(crate) fn parse(input: &str) -> IResult<&str, SyncTrackEvent> {
-        let (input, time) = nom::character::complete::u32(input)?;
+        let (input, time) = map(nom::character::complete::u32, Tick::new)(input)?;
         let (input, _) = tag(" = ")(input)?;
         let (input, result) = alt((
             map(
@@ -0,0 +1,131 @@
+use crate::{sync_track::SyncTrack, sync_track_event::SyncTrackEvent, tick::Tick};
+
+/// BPM assumed before the first `B` marker, in thousandths of a beat per minute.
+pub(crate) const DEFAULT_BPM: u32 = 120_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    tick: Tick,
+    seconds: f64,
+    bpm: u32,
+}
+
+/// Conversion between ticks and seconds following the BPM markers of a chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    resolution: u32,
+    segments: Vec<Segment>,
+}
+
+impl TempoMap {
+    #[must_use]
+    pub(crate) fn new(resolution: u32, synctrack: &SyncTrack) -> Self {
+        let mut bpms: Vec<(Tick, u32)> = synctrack
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                SyncTrackEvent::Bpm { time, value } if *value > 0 => Some((*time, *value)),
+                _ => None,
+            })
+            .collect();
+        bpms.sort_by_key(|(time, _)| *time);
+        let mut segments = Vec::with_capacity(bpms.len() + 1);
+        let mut current = Segment {
+            tick: Tick::ZERO,
+            seconds: 0.0,
+            bpm: DEFAULT_BPM,
+        };
+        for (tick, bpm) in bpms {
+            if tick == current.tick {
+                current.bpm = bpm;
+            } else {
+                segments.push(current);
+                current = Segment {
+                    tick,
+                    seconds: current.seconds
+                        + span_seconds(tick.distance(current.tick), current.bpm, resolution),
+                    bpm,
+                };
+            }
+        }
+        segments.push(current);
+        Self {
+            resolution,
+            segments,
+        }
+    }
+
+    #[must_use]
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    fn segment_at_tick(&self, tick: f64) -> &Segment {
+        let index = self
+            .segments
+            .partition_point(|segment| f64::from(segment.tick) <= tick);
+        &self.segments[index.saturating_sub(1)]
+    }
+
+    /// BPM in effect at the given tick, in thousandths of a beat per minute.
+    #[must_use]
+    pub fn bpm_at(&self, tick: Tick) -> u32 {
+        self.segment_at_tick(f64::from(tick)).bpm
+    }
+
+    /// Absolute time of the given tick in seconds.
+    #[must_use]
+    pub fn seconds_at(&self, tick: Tick) -> f64 {
+        self.seconds_at_fractional(f64::from(tick))
+    }
+
+    /// Absolute time of a possibly fractional tick in seconds.
+    #[must_use]
+    pub fn seconds_at_fractional(&self, tick: f64) -> f64 {
+        let segment = self.segment_at_tick(tick);
+        segment.seconds
+            + (tick - f64::from(segment.tick)) * 60_000.0
+                / (f64::from(self.resolution) * f64::from(segment.bpm))
+    }
+
+    /// Possibly fractional tick at the given absolute time in seconds.
+    #[must_use]
+    pub fn tick_at(&self, seconds: f64) -> f64 {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.seconds <= seconds);
+        let segment = &self.segments[index.saturating_sub(1)];
+        f64::from(segment.tick)
+            + (seconds - segment.seconds) * f64::from(self.resolution) * f64::from(segment.bpm)
+                / 60_000.0
+    }
+}
+
+fn span_seconds(ticks: u32, bpm: u32, resolution: u32) -> f64 {
+    f64::from(ticks) * 60_000.0 / (f64::from(resolution) * f64::from(bpm))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_tempo_map() {
+        let synctrack = SyncTrack::parse(
+            "[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+  768 = B 60000
+}",
+        )
+        .unwrap()
+        .1;
+        let tempo_map = TempoMap::new(192, &synctrack);
+        assert!((tempo_map.seconds_at(Tick::new(768)) - 2.0).abs() < 1e-9);
+        assert!((tempo_map.seconds_at(Tick::new(960)) - 3.0).abs() < 1e-9);
+        assert!((tempo_map.tick_at(3.0) - 960.0).abs() < 1e-9);
+        assert_eq!(tempo_map.bpm_at(Tick::new(800)), 60000);
+    }
+}
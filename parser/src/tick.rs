@@ -0,0 +1,92 @@
+use std::fmt::Display;
+
+/// A position in a chart, counted in ticks from the start of the song.
+///
+/// Arithmetic is only available through checked operations, so that
+/// rescaling a long chart reports an error instead of silently wrapping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Tick(u32);
+
+impl Tick {
+    pub const ZERO: Tick = Tick(0);
+
+    #[must_use]
+    pub const fn new(value: u32) -> Self {
+        Self(value)
+    }
+
+    #[must_use]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// The tick `ticks` later, or `None` on overflow.
+    #[must_use]
+    pub fn checked_add(self, ticks: u32) -> Option<Tick> {
+        self.0.checked_add(ticks).map(Tick)
+    }
+
+    /// The tick `ticks` later, clamped to the largest representable tick.
+    #[must_use]
+    pub fn saturating_add(self, ticks: u32) -> Tick {
+        Tick(self.0.saturating_add(ticks))
+    }
+
+    /// The tick `ticks` earlier, or `None` if that is before the start.
+    #[must_use]
+    pub fn checked_sub(self, ticks: u32) -> Option<Tick> {
+        self.0.checked_sub(ticks).map(Tick)
+    }
+
+    /// The tick multiplied by `factor`, or `None` on overflow.
+    #[must_use]
+    pub fn checked_mul(self, factor: u32) -> Option<Tick> {
+        self.0.checked_mul(factor).map(Tick)
+    }
+
+    /// Number of ticks between `self` and `other`, in either order.
+    #[must_use]
+    pub fn distance(self, other: Tick) -> u32 {
+        self.0.abs_diff(other.0)
+    }
+}
+
+impl From<u32> for Tick {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Tick> for u32 {
+    fn from(value: Tick) -> Self {
+        value.0
+    }
+}
+
+impl From<Tick> for f64 {
+    fn from(value: Tick) -> Self {
+        f64::from(value.0)
+    }
+}
+
+impl Display for Tick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick() {
+        let tick = Tick::new(u32::MAX / 2 + 1);
+        assert_eq!(tick.checked_mul(2), None);
+        assert_eq!(tick.checked_add(u32::MAX / 2), Some(Tick::new(u32::MAX)));
+        assert_eq!(Tick::ZERO.checked_sub(1), None);
+        assert_eq!(Tick::new(3).distance(Tick::new(5)), 2);
+    }
+}
@@ -0,0 +1,235 @@
+use std::ops::Range;
+
+use crate::{
+    chart::Chart, rescale::RescaleError, sync_track_event::SyncTrackEvent, tick::Tick,
+    track_event::TrackEvent,
+};
+
+pub(crate) fn insert_time(chart: &mut Chart, at: Tick, ticks: u32) -> Result<(), RescaleError> {
+    let shift = |time: Tick| {
+        if time >= at {
+            time.checked_add(ticks).ok_or(time)
+        } else {
+            Ok(time)
+        }
+    };
+    let mut edited = chart.clone();
+    for event in edited.synctrack_mut().events_mut() {
+        let time = event.time_mut();
+        *time = shift(*time).map_err(|time| RescaleError::event("SyncTrack", time))?;
+    }
+    for event in edited.global_events_mut().events_mut() {
+        let time = event.time_mut();
+        *time = shift(*time).map_err(|time| RescaleError::event("Events", time))?;
+    }
+    for track in edited.tracks_mut() {
+        let name = track.name().to_string();
+        for event in track.events_mut() {
+            let crosses = event.time() < at && event.end() > at;
+            match event {
+                TrackEvent::Note { time, sustain, .. } if crosses => {
+                    *sustain = at.distance(*time);
+                }
+                TrackEvent::Special { time, content, .. } if crosses => {
+                    *content = content
+                        .checked_add(ticks)
+                        .ok_or_else(|| RescaleError::event(&name, *time))?;
+                }
+                TrackEvent::Note { time, .. }
+                | TrackEvent::Special { time, .. }
+                | TrackEvent::Event { time, .. } => {
+                    *time = shift(*time).map_err(|time| RescaleError::event(&name, time))?;
+                }
+            }
+        }
+    }
+    *chart = edited;
+    Ok(())
+}
+
+pub(crate) fn remove_time(chart: &mut Chart, range: Range<Tick>) {
+    if range.end <= range.start {
+        return;
+    }
+    let Range { start, end } = range;
+    let removed = end.distance(start);
+    let shift = |time: Tick| {
+        if time >= end {
+            Tick::new(time.get() - removed)
+        } else {
+            time
+        }
+    };
+
+    let events = chart.synctrack_mut().events_mut();
+    // the last BPM and time signature inside the cut stay in effect after it
+    let kinds: [fn(&SyncTrackEvent) -> bool; 2] = [
+        |event| matches!(event, SyncTrackEvent::Bpm { .. }),
+        |event| matches!(event, SyncTrackEvent::TimeSignature { .. }),
+    ];
+    let mut carried = vec![];
+    for is_kind in kinds {
+        let has_marker_at_end = events
+            .iter()
+            .any(|event| is_kind(event) && event.time() == end);
+        let last_inside = events
+            .iter()
+            .rposition(|event| is_kind(event) && (start..end).contains(&event.time()));
+        if let (false, Some(index)) = (has_marker_at_end, last_inside) {
+            carried.push(index);
+        }
+    }
+    let mut index = 0;
+    events.retain_mut(|event| {
+        let keep = if carried.contains(&index) {
+            *event.time_mut() = start;
+            true
+        } else if (start..end).contains(&event.time()) {
+            false
+        } else {
+            *event.time_mut() = shift(event.time());
+            true
+        };
+        index += 1;
+        keep
+    });
+    events.sort_by_key(|event| (event.time(), event.type_rank()));
+
+    chart.global_events_mut().events_mut().retain_mut(|event| {
+        let time = event.time_mut();
+        if (start..end).contains(time) {
+            return false;
+        }
+        *time = shift(*time);
+        true
+    });
+
+    for track in chart.tracks_mut() {
+        track.events_mut().retain_mut(|event| {
+            let event_end = event.end();
+            match event {
+                TrackEvent::Note {
+                    time,
+                    sustain: length,
+                    ..
+                }
+                | TrackEvent::Special {
+                    time,
+                    content: length,
+                    ..
+                } if *time < start => {
+                    if event_end > start {
+                        let new_end = if event_end >= end {
+                            shift(event_end)
+                        } else {
+                            start
+                        };
+                        *length = new_end.distance(*time);
+                    }
+                    true
+                }
+                TrackEvent::Special { time, content, .. } if *time < end => {
+                    // a phrase starting inside the cut keeps the part after it
+                    if event_end > end {
+                        *time = start;
+                        *content = event_end.distance(end);
+                        true
+                    } else {
+                        false
+                    }
+                }
+                TrackEvent::Note { time, .. }
+                | TrackEvent::Special { time, .. }
+                | TrackEvent::Event { time, .. } => {
+                    if (start..end).contains(time) {
+                        return false;
+                    }
+                    *time = shift(*time);
+                    true
+                }
+            }
+        });
+        track
+            .events_mut()
+            .sort_by_key(|event| (event.time(), event.type_rank()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const CHART: &str = "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+  400 = B 150000
+  500 = TS 3
+}
+[Events]
+{
+  0 = E \"section Intro\"
+  450 = E \"section Gone\"
+  768 = E \"section Verse\"
+}
+[ExpertSingle]
+{
+  0 = N 0 900
+  192 = S 2 400
+  384 = N 1 0
+  450 = S 2 400
+  768 = N 2 100
+}
+";
+
+    #[test]
+    fn test_insert_time() {
+        let mut chart = Chart::parse(CHART).unwrap().1;
+        chart.insert_time(Tick::new(384), 192).unwrap();
+        let written = chart.to_string();
+        assert!(written.contains("  0 = N 0 384\n"));
+        assert!(written.contains("  192 = S 2 592\n"));
+        assert!(written.contains("  576 = N 1 0\n"));
+        assert!(written.contains("  592 = B 150000\n"));
+        assert!(written.contains("  960 = E \"section Verse\"\n"));
+    }
+
+    #[test]
+    fn test_remove_time() {
+        let mut chart = Chart::parse(CHART).unwrap().1;
+        chart.remove_time(Tick::new(384)..Tick::new(576));
+        assert_eq!(
+            chart.to_string(),
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+  384 = TS 3
+  384 = B 150000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+  576 = E \"section Verse\"
+}
+
+[ExpertSingle]
+{
+  0 = N 0 708
+  192 = S 2 208
+  384 = S 2 274
+  576 = N 2 100
+}
+"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{sync_track::SyncTrack, sync_track_event::SyncTrackEvent, tick::Tick};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    tick: Tick,
+    numerator: u32,
+    denominator: u32,
+}
+
+/// Measure and beat positions following the `TS` markers of a chart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSignatureMap {
+    resolution: u32,
+    segments: Vec<Segment>,
+}
+
+impl TimeSignatureMap {
+    #[must_use]
+    pub(crate) fn new(resolution: u32, synctrack: &SyncTrack) -> Self {
+        let mut signatures: Vec<Segment> = synctrack
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                SyncTrackEvent::TimeSignature {
+                    time,
+                    value1,
+                    value2,
+                } if *value1 > 0 => Some(Segment {
+                    tick: *time,
+                    numerator: *value1,
+                    denominator: 1 << value2.unwrap_or(2).min(31),
+                }),
+                _ => None,
+            })
+            .collect();
+        signatures.sort_by_key(|segment| segment.tick);
+        let mut segments = vec![Segment {
+            tick: Tick::ZERO,
+            numerator: 4,
+            denominator: 4,
+        }];
+        for signature in signatures {
+            match segments.last_mut() {
+                Some(last) if last.tick == signature.tick => *last = signature,
+                _ => segments.push(signature),
+            }
+        }
+        Self {
+            resolution,
+            segments,
+        }
+    }
+
+    fn segment_index(&self, tick: Tick) -> usize {
+        self.segments
+            .partition_point(|segment| segment.tick <= tick)
+            .saturating_sub(1)
+    }
+
+    /// Time signature in effect at the given tick, as (numerator, denominator).
+    #[must_use]
+    pub fn signature_at(&self, tick: Tick) -> (u32, u32) {
+        let segment = self.segments[self.segment_index(tick)];
+        (segment.numerator, segment.denominator)
+    }
+
+    /// Length of a beat in ticks at the given tick.
+    #[must_use]
+    pub fn beat_length(&self, tick: Tick) -> f64 {
+        let segment = self.segments[self.segment_index(tick)];
+        f64::from(self.resolution) * 4.0 / f64::from(segment.denominator)
+    }
+
+    /// Length of a measure in ticks at the given tick.
+    #[must_use]
+    pub fn measure_length(&self, tick: Tick) -> f64 {
+        let (numerator, _) = self.signature_at(tick);
+        f64::from(numerator) * self.beat_length(tick)
+    }
+
+    /// Start of the measure containing the given tick. Measures restart at
+    /// every time signature change.
+    #[must_use]
+    pub fn measure_start(&self, tick: Tick) -> Tick {
+        let start = self.segments[self.segment_index(tick)].tick;
+        let length = self.measure_length(tick);
+        let offset = f64::from(tick.distance(start));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let measures = ((offset / length).floor() * length).round() as u32;
+        Tick::new(start.get() + measures.min(tick.distance(start)))
+    }
+
+    /// One-based number of the measure containing the given tick. A time
+    /// signature change in the middle of a measure starts a new one.
+    #[must_use]
+    pub fn measure_number(&self, tick: Tick) -> u32 {
+        let index = self.segment_index(tick);
+        let measures_before: f64 = self.segments[..=index]
+            .windows(2)
+            .map(|pair| {
+                let length = self.measure_length(pair[0].tick);
+                (f64::from(pair[1].tick.distance(pair[0].tick)) / length).ceil()
+            })
+            .sum();
+        let start = self.segments[index].tick;
+        let measures = (f64::from(tick.distance(start)) / self.measure_length(tick)).floor();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let number = (measures_before + measures) as u32 + 1;
+        number
+    }
+
+    /// Nearest point of a grid dividing a whole note into `subdivision` equal
+    /// parts, restarted at every time signature change.
+    #[must_use]
+    pub fn snap(&self, tick: Tick, subdivision: u32) -> Tick {
+        let index = self.segment_index(tick);
+        let start = self.segments[index].tick;
+        let step = f64::from(self.resolution) * 4.0 / f64::from(subdivision.max(1));
+        let offset = f64::from(tick.distance(start));
+        let snapped = ((offset / step).round() * step).round();
+        // a tick past the largest representable value cannot be closer than the original
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let snapped = start.checked_add(snapped as u32).unwrap_or(tick);
+        // the next time signature change restarts the grid, so it is a grid point too
+        match self.segments.get(index + 1) {
+            Some(next) if next.tick.distance(tick) < snapped.distance(tick) => next.tick,
+            _ => snapped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_snap() {
+        let synctrack = SyncTrack::parse(
+            "[SyncTrack]
+{
+  0 = TS 7 3
+  0 = B 120000
+  672 = TS 4
+}",
+        )
+        .unwrap()
+        .1;
+        let map = TimeSignatureMap::new(192, &synctrack);
+        assert_eq!(map.signature_at(Tick::new(700)), (4, 4));
+        assert_eq!(map.snap(Tick::new(50), 16), Tick::new(48));
+        assert_eq!(map.snap(Tick::new(70), 12), Tick::new(64));
+        assert_eq!(map.snap(Tick::new(670), 4), Tick::new(672));
+        assert_eq!(map.snap(Tick::new(690), 16), Tick::new(672));
+        assert_eq!(map.measure_start(Tick::new(600)), Tick::ZERO);
+        assert_eq!(map.measure_start(Tick::new(700)), Tick::new(672));
+        assert_eq!(map.measure_start(Tick::new(1500)), Tick::new(1440));
+        assert_eq!(map.measure_number(Tick::new(671)), 1);
+        assert_eq!(map.measure_number(Tick::new(672)), 2);
+        assert_eq!(map.measure_number(Tick::new(1500)), 3);
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display};
 
 use nom::{
     character::complete::{alpha1, multispace1},
@@ -10,25 +10,47 @@ use nom::{
 
 use crate::{
     components::{curlied, spaced, squared},
+    rescale::TickMap,
+    tick::Tick,
     track_event::TrackEvent,
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Track<'a> {
-    name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     events: Vec<TrackEvent<'a>>,
 }
 
 impl<'a> Track<'a> {
     #[must_use]
-    pub(crate) fn new(name: &'a str, events: Vec<TrackEvent<'a>>) -> Self {
-        Self { name, events }
+    pub(crate) fn new(name: impl Into<Cow<'a, str>>, events: Vec<TrackEvent<'a>>) -> Self {
+        Self {
+            name: name.into(),
+            events,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn events(&self) -> &[TrackEvent<'a>] {
+        &self.events
+    }
+
+    pub(crate) fn events_mut(&mut self) -> &mut Vec<TrackEvent<'a>> {
+        &mut self.events
     }
 
-    pub(crate) fn multiply(&mut self, factor: u32) {
+    /// Rescale every event, returning the original time of the first one that overflows.
+    pub(crate) fn rescale(&mut self, map: &TickMap) -> Result<(), Tick> {
         for item in &mut self.events {
-            item.multiply(factor);
+            item.rescale(map)?;
         }
+        Ok(())
     }
 
     #[inline]
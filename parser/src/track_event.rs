@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display};
 
 use nom::{
     branch::alt,
@@ -9,31 +9,87 @@ use nom::{
     IResult,
 };
 
-#[derive(Debug, PartialEq, Eq)]
+use crate::{rescale::TickMap, song_property::unquote, tick::Tick};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TrackEvent<'a> {
-    Note { time: u32, fret: u32, sustain: u32 },
-    Special { time: u32, kind: u32, content: u32 },
-    Event { time: u32, value: &'a str },
+    Note {
+        time: Tick,
+        fret: u32,
+        sustain: u32,
+    },
+    Special {
+        time: Tick,
+        kind: u32,
+        content: u32,
+    },
+    Event {
+        time: Tick,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        value: Cow<'a, str>,
+    },
 }
 
 impl<'a> TrackEvent<'a> {
-    pub(crate) fn multiply(&mut self, factor: u32) {
+    pub(crate) fn rescale(&mut self, map: &TickMap) -> Result<(), Tick> {
         match self {
-            TrackEvent::Note { time, sustain, .. } => {
-                *time *= factor;
-                *sustain *= factor;
+            TrackEvent::Note {
+                time,
+                sustain: length,
+                ..
             }
-            TrackEvent::Special { time, content, .. } => {
-                *time *= factor;
-                *content *= factor;
+            | TrackEvent::Special {
+                time,
+                content: length,
+                ..
+            } => {
+                *length = map.length(*time, *length).ok_or(*time)?;
+                *time = map.time(*time).ok_or(*time)?;
             }
-            TrackEvent::Event { time, .. } => *time *= factor,
+            TrackEvent::Event { time, .. } => *time = map.time(*time).ok_or(*time)?,
+        }
+        Ok(())
+    }
+
+    pub(crate) fn time(&self) -> Tick {
+        match self {
+            TrackEvent::Note { time, .. }
+            | TrackEvent::Special { time, .. }
+            | TrackEvent::Event { time, .. } => *time,
+        }
+    }
+
+    /// Sustain or phrase length, zero for text events.
+    pub(crate) fn length(&self) -> u32 {
+        match self {
+            TrackEvent::Note { sustain, .. } => *sustain,
+            TrackEvent::Special { content, .. } => *content,
+            TrackEvent::Event { .. } => 0,
+        }
+    }
+
+    /// Order of events sharing a tick in a canonical .chart file.
+    pub(crate) fn type_rank(&self) -> u8 {
+        match self {
+            TrackEvent::Note { .. } => 0,
+            TrackEvent::Special { .. } => 1,
+            TrackEvent::Event { .. } => 2,
+        }
+    }
+
+    /// Tick at which the event ends, including sustains and phrase lengths.
+    pub(crate) fn end(&self) -> Tick {
+        match self {
+            TrackEvent::Note { time, sustain, .. } => time.saturating_add(*sustain),
+            TrackEvent::Special { time, content, .. } => time.saturating_add(*content),
+            TrackEvent::Event { time, .. } => *time,
         }
     }
 
     #[inline]
     pub(crate) fn parse(input: &str) -> IResult<&str, TrackEvent<'_>> {
-        let (input, time) = nom::character::complete::u32(input)?;
+        let (input, time) = map(nom::character::complete::u32, Tick::new)(input)?;
         let (input, _) = tag(" = ")(input)?;
         let (input, result) = alt((
             map(
@@ -51,8 +107,12 @@ impl<'a> TrackEvent<'a> {
                     sustain,
                 },
             ),
-            map(preceded(tag("E "), not_line_ending), |value| {
-                TrackEvent::Event { time, value }
+            // read the same way as global events, see `GlobalEvent::parse`
+            map(preceded(tag("E "), not_line_ending), |value: &str| {
+                TrackEvent::Event {
+                    time,
+                    value: Cow::Borrowed(unquote(value.trim_end())),
+                }
             }),
             map(
                 preceded(
@@ -87,6 +147,14 @@ impl<'a> Display for TrackEvent<'a> {
                 kind,
                 content,
             } => writeln!(f, "  {time} = S {kind} {content}"),
+            // track events are written bare, unless that would not read back
+            TrackEvent::Event { time, value }
+                if value.is_empty()
+                    || value.starts_with('"')
+                    || value.ends_with(['"', ' ', '\t']) =>
+            {
+                writeln!(f, "  {time} = E \"{value}\"")
+            }
             TrackEvent::Event { time, value } => writeln!(f, "  {time} = E {value}"),
         }
     }
@@ -101,4 +169,29 @@ mod tests {
     fn test_track_event() {
         TrackEvent::parse("183936 = N 4 3072").unwrap();
     }
+
+    #[test]
+    fn test_event_quoting() {
+        fn parse(line: &str) -> TrackEvent<'_> {
+            TrackEvent::parse(line).unwrap().1
+        }
+
+        let solo = TrackEvent::Event {
+            time: Tick::ZERO,
+            value: "solo".into(),
+        };
+        assert_eq!(parse("0 = E solo"), solo);
+        assert_eq!(parse("0 = E \"solo\""), solo);
+        assert_eq!(solo.to_string(), "  0 = E solo\n");
+
+        for value in [
+            "", "solo ", "\"solo\"", "a \"b\"", "a\\\"b", "x\\\"y ", "ソロ",
+        ] {
+            let event = TrackEvent::Event {
+                time: Tick::ZERO,
+                value: value.into(),
+            };
+            assert_eq!(parse(event.to_string().trim_start()), event);
+        }
+    }
 }
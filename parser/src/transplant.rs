@@ -0,0 +1,295 @@
+use std::fmt::Display;
+
+use crate::{
+    chart::Chart, global_event::GlobalEvent, rescale::RescaleError, tempo::TempoMap, tick::Tick,
+    time_signature::TimeSignatureMap, track::Track, track_event::TrackEvent,
+};
+
+/// An event that [`Chart::transplant_track`] or
+/// [`Chart::transplant_lyrics`] placed between grid points of the target chart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OffGridEvent {
+    /// Name of the section, without brackets.
+    pub section: String,
+    /// Tick in the source chart.
+    pub from: Tick,
+    /// Tick in the target chart.
+    pub to: Tick,
+    /// Distance to the nearest grid point in the target chart.
+    pub distance: u32,
+}
+
+/// Summary of [`Chart::transplant_track`] and [`Chart::transplant_lyrics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransplantReport {
+    pub moved: usize,
+    pub off_grid: Vec<OffGridEvent>,
+}
+
+/// Error returned by [`Chart::transplant_track`] and
+/// [`Chart::transplant_lyrics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransplantError {
+    /// The source chart has no track with the given name.
+    MissingTrack(String),
+    /// An event lands beyond the largest representable tick.
+    Rescale(RescaleError),
+}
+
+impl From<RescaleError> for TransplantError {
+    fn from(error: RescaleError) -> Self {
+        TransplantError::Rescale(error)
+    }
+}
+
+impl Display for TransplantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransplantError::MissingTrack(name) => write!(f, "no [{name}] track to transplant"),
+            TransplantError::Rescale(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TransplantError {}
+
+/// Converts ticks of one chart to ticks of another through absolute time.
+struct Retimer {
+    source: TempoMap,
+    target: TempoMap,
+    grid: TimeSignatureMap,
+    subdivision: u32,
+}
+
+impl Retimer {
+    fn new(source: &Chart, target: &Chart, subdivision: u32) -> Self {
+        Self {
+            source: source.tempo_map(),
+            target: target.tempo_map(),
+            grid: target.time_signature_map(),
+            subdivision,
+        }
+    }
+
+    fn tick(&self, section: &str, time: Tick) -> Result<Tick, RescaleError> {
+        let tick = self
+            .target
+            .tick_at(self.source.seconds_at(time))
+            .round()
+            .max(0.0);
+        if tick > f64::from(u32::MAX) {
+            return Err(RescaleError::event(section, time));
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Ok(Tick::new(tick as u32))
+    }
+
+    /// Retime the start of an event, reporting it if it lands off the grid.
+    fn start(
+        &self,
+        section: &str,
+        time: Tick,
+        report: &mut TransplantReport,
+    ) -> Result<Tick, RescaleError> {
+        let new_time = self.tick(section, time)?;
+        let distance = self
+            .grid
+            .snap(new_time, self.subdivision)
+            .distance(new_time);
+        if distance > 0 {
+            report.off_grid.push(OffGridEvent {
+                section: section.to_string(),
+                from: time,
+                to: new_time,
+                distance,
+            });
+        }
+        report.moved += 1;
+        Ok(new_time)
+    }
+}
+
+pub(crate) fn transplant_track<'a>(
+    target: &mut Chart<'a>,
+    source: &Chart<'a>,
+    name: &str,
+    subdivision: u32,
+) -> Result<TransplantReport, TransplantError> {
+    let track = source
+        .tracks()
+        .iter()
+        .find(|track| track.name() == name)
+        .ok_or_else(|| TransplantError::MissingTrack(name.to_string()))?;
+    let retimer = Retimer::new(source, target, subdivision);
+    let mut report = TransplantReport::default();
+    let mut events = Vec::with_capacity(track.events().len());
+    for event in track.events() {
+        let event_end = event.end();
+        let mut event = event.clone();
+        match &mut event {
+            TrackEvent::Note {
+                time,
+                sustain: length,
+                ..
+            }
+            | TrackEvent::Special {
+                time,
+                content: length,
+                ..
+            } => {
+                let new_time = retimer.start(name, *time, &mut report)?;
+                if *length > 0 {
+                    *length = retimer.tick(name, event_end)?.distance(new_time);
+                }
+                *time = new_time;
+            }
+            TrackEvent::Event { time, .. } => *time = retimer.start(name, *time, &mut report)?,
+        }
+        events.push(event);
+    }
+    let track = Track::new(name.to_string(), events);
+    let tracks = target.tracks_mut();
+    match tracks.iter_mut().find(|existing| existing.name() == name) {
+        Some(existing) => *existing = track,
+        None => tracks.push(track),
+    }
+    Ok(report)
+}
+
+pub(crate) fn transplant_lyrics<'a>(
+    target: &mut Chart<'a>,
+    source: &Chart<'a>,
+    subdivision: u32,
+) -> Result<TransplantReport, TransplantError> {
+    let is_lyric = |event: &GlobalEvent| {
+        matches!(
+            event,
+            GlobalEvent::PhraseStart { .. }
+                | GlobalEvent::PhraseEnd { .. }
+                | GlobalEvent::Lyric { .. }
+        )
+    };
+    let retimer = Retimer::new(source, target, subdivision);
+    let mut report = TransplantReport::default();
+    let mut lyrics = vec![];
+    for event in source
+        .global_events()
+        .events()
+        .iter()
+        .filter(|event| is_lyric(event))
+    {
+        let mut event = event.clone();
+        let time = event.time_mut();
+        *time = retimer.start("Events", *time, &mut report)?;
+        lyrics.push(event);
+    }
+    let events = target.global_events_mut().events_mut();
+    events.retain(|event| !is_lyric(event));
+    events.append(&mut lyrics);
+    events.sort_by_key(GlobalEvent::time);
+    Ok(report)
+}
+
+impl Display for TransplantReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Moved {} events", self.moved)?;
+        for event in &self.off_grid {
+            writeln!(
+                f,
+                "[{}]: event from tick {} lands on {}, {} ticks off the grid",
+                event.section, event.from, event.to, event.distance
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const SOURCE: &str = "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+}
+[Events]
+{
+  384 = E \"phrase_start\"
+  384 = E \"lyric Hi\"
+  400 = E \"phrase_end\"
+}
+[ExpertDrums]
+{
+  192 = N 0 0
+  384 = N 1 192
+  400 = N 2 0
+}
+";
+
+    const TARGET: &str = "[Song]
+{
+  Resolution = 480
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 60000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+  100 = E \"lyric old\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+}
+";
+
+    #[test]
+    fn test_transplant_track() {
+        let source = Chart::parse(SOURCE).unwrap().1;
+        let mut target = Chart::parse(TARGET).unwrap().1;
+        let report = target.transplant_track(&source, "ExpertDrums", 16).unwrap();
+        assert_eq!(report.moved, 3);
+        assert_eq!(
+            report.off_grid,
+            vec![OffGridEvent {
+                section: "ExpertDrums".to_string(),
+                from: Tick::new(400),
+                to: Tick::new(500),
+                distance: 20,
+            }]
+        );
+        let written = target.to_string();
+        assert!(
+            written.contains("[ExpertDrums]\n{\n  240 = N 0 0\n  480 = N 1 240\n  500 = N 2 0\n}")
+        );
+        assert!(matches!(
+            target.transplant_track(&source, "HardDrums", 16),
+            Err(TransplantError::MissingTrack(_))
+        ));
+    }
+
+    #[test]
+    fn test_transplant_lyrics() {
+        let source = Chart::parse(SOURCE).unwrap().1;
+        let mut target = Chart::parse(TARGET).unwrap().1;
+        let report = target.transplant_lyrics(&source, 16).unwrap();
+        assert_eq!(report.moved, 3);
+        assert_eq!(report.off_grid.len(), 1);
+        let written = target.to_string();
+        assert!(!written.contains("lyric old"));
+        assert!(written.contains(
+            "  0 = E \"section Intro\"\n  480 = E \"phrase_start\"\n  480 = E \"lyric Hi\"\n  500 = E \"phrase_end\"\n"
+        ));
+    }
+}
@@ -0,0 +1,390 @@
+use std::{collections::HashSet, fmt::Display, ops::Range};
+
+use crate::{
+    chart::Chart,
+    global_event::GlobalEvent,
+    instrument::{parse_track_name, track_name, Difficulty, Instrument},
+    star_power::{gem_times, notes_in, phrases},
+    sync_track_event::SyncTrackEvent,
+    tick::Tick,
+    track::Track,
+    track_event::TrackEvent,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Something that makes a chart unplayable or likely to misbehave.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum Problem {
+    ZeroResolution,
+    MissingInitialBpm,
+    MissingInitialTimeSignature,
+    /// The event comes before the one preceding it in the file.
+    Unsorted {
+        previous: Tick,
+    },
+    DuplicateNote {
+        fret: u32,
+    },
+    /// The sustain runs past the start of the next note.
+    OverlappingSustain {
+        next: Tick,
+    },
+    EmptyStarPower,
+    /// A star power phrase has no counterpart on the Expert track of the
+    /// same instrument, or the other way around.
+    StarPowerMismatch {
+        reference: String,
+    },
+    UnknownTrack,
+}
+
+/// A single finding of [`Chart::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Name of the section the problem was found in, without brackets.
+    pub section: String,
+    pub tick: Option<Tick>,
+    pub problem: Problem,
+}
+
+impl Diagnostic {
+    fn new(section: &str, tick: Option<Tick>, problem: Problem) -> Self {
+        let severity = match problem {
+            Problem::ZeroResolution | Problem::MissingInitialBpm | Problem::Unsorted { .. } => {
+                Severity::Error
+            }
+            Problem::MissingInitialTimeSignature
+            | Problem::DuplicateNote { .. }
+            | Problem::OverlappingSustain { .. }
+            | Problem::EmptyStarPower
+            | Problem::StarPowerMismatch { .. }
+            | Problem::UnknownTrack => Severity::Warning,
+        };
+        Self {
+            severity,
+            section: section.to_string(),
+            tick,
+            problem,
+        }
+    }
+}
+
+/// Check a chart for problems that parsing alone does not catch.
+pub(crate) fn validate(chart: &Chart) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    if chart.resolution() == 0 {
+        diagnostics.push(Diagnostic::new("Song", None, Problem::ZeroResolution));
+    }
+
+    let synctrack = chart.synctrack().events();
+    if !synctrack.iter().any(|event| {
+        matches!(
+            event,
+            SyncTrackEvent::Bpm {
+                time: Tick::ZERO,
+                ..
+            }
+        )
+    }) {
+        diagnostics.push(Diagnostic::new(
+            "SyncTrack",
+            Some(Tick::ZERO),
+            Problem::MissingInitialBpm,
+        ));
+    }
+    if !synctrack.iter().any(|event| {
+        matches!(
+            event,
+            SyncTrackEvent::TimeSignature {
+                time: Tick::ZERO,
+                ..
+            }
+        )
+    }) {
+        diagnostics.push(Diagnostic::new(
+            "SyncTrack",
+            Some(Tick::ZERO),
+            Problem::MissingInitialTimeSignature,
+        ));
+    }
+    check_sorted(
+        "SyncTrack",
+        synctrack.iter().map(SyncTrackEvent::time),
+        &mut diagnostics,
+    );
+    check_sorted(
+        "Events",
+        chart.global_events().events().iter().map(GlobalEvent::time),
+        &mut diagnostics,
+    );
+    for track in chart.tracks() {
+        validate_track(track, &mut diagnostics);
+    }
+    validate_star_power(chart, &mut diagnostics);
+    diagnostics
+}
+
+fn check_sorted(section: &str, times: impl Iterator<Item = Tick>, out: &mut Vec<Diagnostic>) {
+    let mut previous = Tick::ZERO;
+    for time in times {
+        if time < previous {
+            out.push(Diagnostic::new(
+                section,
+                Some(time),
+                Problem::Unsorted { previous },
+            ));
+        }
+        previous = previous.max(time);
+    }
+}
+
+fn validate_track(track: &Track, out: &mut Vec<Diagnostic>) {
+    let name = track.name();
+    let instrument = if let Some((_, instrument)) = parse_track_name(name) {
+        instrument
+    } else {
+        out.push(Diagnostic::new(name, None, Problem::UnknownTrack));
+        Instrument::Single
+    };
+    check_sorted(name, track.events().iter().map(TrackEvent::time), out);
+
+    let mut seen = HashSet::new();
+    let mut gems = vec![];
+    for event in track.events() {
+        if let TrackEvent::Note {
+            time,
+            fret,
+            sustain,
+        } = event
+        {
+            if !seen.insert((*time, *fret)) {
+                out.push(Diagnostic::new(
+                    name,
+                    Some(*time),
+                    Problem::DuplicateNote { fret: *fret },
+                ));
+            }
+            if instrument.is_gem(*fret) {
+                gems.push((*time, *sustain));
+            }
+        }
+    }
+    gems.sort_unstable();
+
+    for (index, (time, sustain)) in gems.iter().enumerate() {
+        let next = gems[index..]
+            .iter()
+            .map(|(next, _)| *next)
+            .find(|next| next > time);
+        if let Some(next) = next {
+            if *sustain > 0 && time.saturating_add(*sustain) > next {
+                out.push(Diagnostic::new(
+                    name,
+                    Some(*time),
+                    Problem::OverlappingSustain { next },
+                ));
+            }
+        }
+    }
+
+    // a phrase of length 0 still covers its own tick, as in `validate_star_power`
+    let times = gem_times(track, instrument);
+    for phrase in phrases(track) {
+        if notes_in(&times, &phrase).is_empty() {
+            out.push(Diagnostic::new(
+                name,
+                Some(phrase.start),
+                Problem::EmptyStarPower,
+            ));
+        }
+    }
+}
+
+/// Compare the star power phrases of every lower difficulty with those of
+/// the Expert track of the same instrument. Expert phrases holding no notes of
+/// the lower track are not expected there.
+fn validate_star_power(chart: &Chart, out: &mut Vec<Diagnostic>) {
+    let overlaps = |a: &Range<Tick>, b: &Range<Tick>| a.start < b.end && b.start < a.end;
+    for track in chart.tracks() {
+        let Some((difficulty, instrument)) = parse_track_name(track.name()) else {
+            continue;
+        };
+        let reference_name = track_name(Difficulty::Expert, instrument);
+        let Some(reference) = chart
+            .tracks()
+            .iter()
+            .find(|other| other.name() == reference_name)
+            .filter(|_| difficulty != Difficulty::Expert)
+        else {
+            continue;
+        };
+        let (own, expected) = (phrases(track), phrases(reference));
+        let times = gem_times(track, instrument);
+        let missing = expected.iter().filter(|phrase| {
+            !notes_in(&times, phrase).is_empty() && !own.iter().any(|own| overlaps(own, phrase))
+        });
+        let extra = own
+            .iter()
+            .filter(|phrase| !expected.iter().any(|expected| overlaps(expected, phrase)));
+        let mut ticks: Vec<Tick> = missing.chain(extra).map(|phrase| phrase.start).collect();
+        ticks.sort_unstable();
+        for tick in ticks {
+            out.push(Diagnostic::new(
+                track.name(),
+                Some(tick),
+                Problem::StarPowerMismatch {
+                    reference: reference_name.clone(),
+                },
+            ));
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::ZeroResolution => write!(f, "resolution is zero"),
+            Problem::MissingInitialBpm => write!(f, "no BPM marker at tick 0"),
+            Problem::MissingInitialTimeSignature => write!(f, "no time signature at tick 0"),
+            Problem::Unsorted { previous } => {
+                write!(f, "event comes after an event at tick {previous}")
+            }
+            Problem::DuplicateNote { fret } => write!(f, "duplicate note {fret}"),
+            Problem::OverlappingSustain { next } => {
+                write!(f, "sustain overlaps the next note at tick {next}")
+            }
+            Problem::EmptyStarPower => write!(f, "star power phrase contains no notes"),
+            Problem::StarPowerMismatch { reference } => {
+                write!(f, "star power phrase does not match [{reference}]")
+            }
+            Problem::UnknownTrack => write!(f, "unknown track name"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.tick {
+            Some(tick) => writeln!(
+                f,
+                "{}: [{}] tick {tick}: {}",
+                self.severity, self.section, self.problem
+            ),
+            None => writeln!(f, "{}: [{}]: {}", self.severity, self.section, self.problem),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_validate_track() {
+        let track = Track::parse(
+            "[ExpertSingle]
+{
+  0 = N 0 300
+  0 = N 0 0
+  192 = N 1 0
+  192 = S 2 0
+  96 = N 2 0
+  400 = S 2 100
+}",
+        )
+        .unwrap()
+        .1;
+        let mut diagnostics = vec![];
+        validate_track(&track, &mut diagnostics);
+        let problems: Vec<_> = diagnostics.into_iter().map(|x| x.problem).collect();
+        assert_eq!(
+            problems,
+            vec![
+                Problem::Unsorted {
+                    previous: Tick::new(192)
+                },
+                Problem::DuplicateNote { fret: 0 },
+                Problem::OverlappingSustain {
+                    next: Tick::new(96)
+                },
+                Problem::EmptyStarPower,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_star_power() {
+        let chart = Chart::parse(
+            "[Song]
+{
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  0 = S 2 192
+  384 = N 1 0
+  384 = S 2 96
+}
+[HardSingle]
+{
+  0 = N 0 0
+  96 = S 2 192
+  384 = N 1 0
+}
+[EasySingle]
+{
+  0 = N 0 0
+  0 = S 2 10
+  768 = N 1 0
+  768 = S 2 10
+}
+",
+        )
+        .unwrap()
+        .1;
+        let diagnostics: Vec<_> = chart
+            .validate()
+            .into_iter()
+            .filter(|diagnostic| matches!(diagnostic.problem, Problem::StarPowerMismatch { .. }))
+            .map(|diagnostic| (diagnostic.section, diagnostic.tick))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            vec![
+                ("HardSingle".to_string(), Some(Tick::new(384))),
+                ("EasySingle".to_string(), Some(Tick::new(768))),
+            ]
+        );
+    }
+}